@@ -0,0 +1,29 @@
+use log::info;
+use std::{thread, time::Duration};
+use xcap::Window;
+
+fn main() {
+    pretty_env_logger::init();
+
+    let window = Window::focused().unwrap();
+    println!("Recording window: {:?}", window.title().unwrap());
+
+    {
+        let (video_recorder, sx) = window.video_recorder().unwrap();
+
+        thread::spawn(move || {
+            while let Ok(frame) = sx.recv() {
+                println!("frame: {}x{}", frame.width, frame.height);
+            }
+            info!("frame receiver thread exited")
+        });
+
+        println!("start");
+        video_recorder.start().unwrap();
+        thread::sleep(Duration::from_secs(4));
+        println!("stop");
+        video_recorder.stop().unwrap();
+    }
+
+    std::thread::sleep(Duration::from_millis(10));
+}
@@ -1,6 +1,11 @@
 use crate::{
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    video_recorder::{Frame, VideoRecorderOptions},
+    window::{
+        AppInfo, FrameExtents, PidSource, Rect, WindowCapture, WindowCaptureOptions,
+        WindowListOptions, WindowState, WindowType, Workspace,
+    },
+    window_query::{WindowInfo, WindowInfoFields},
 };
 use image::RgbaImage;
 use std::sync::mpsc::Receiver;
@@ -17,6 +22,10 @@ impl ImplMonitor {
         Err(XCapError::NotSupported)
     }
 
+    pub fn cursor_position() -> XCapResult<(i32, i32)> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn id(&self) -> XCapResult<u32> {
         Err(XCapError::NotSupported)
     }
@@ -41,6 +50,10 @@ impl ImplMonitor {
         Err(XCapError::NotSupported)
     }
 
+    pub fn window_space_rect(&self) -> XCapResult<Rect> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
         Err(XCapError::NotSupported)
     }
@@ -75,7 +88,16 @@ impl ImplMonitor {
         Err(XCapError::NotSupported)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+    pub fn video_recorder(
+        &self,
+        _options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn video_recorders(
+        _options: VideoRecorderOptions,
+    ) -> XCapResult<Vec<(ImplMonitor, ImplVideoRecorder, Receiver<Frame>)>> {
         Err(XCapError::NotSupported)
     }
 }
@@ -88,6 +110,40 @@ impl ImplWindow {
         Ok(Vec::new())
     }
 
+    pub fn all_with(_options: WindowListOptions) -> XCapResult<Vec<ImplWindow>> {
+        Ok(Vec::new())
+    }
+
+    pub fn workspaces() -> XCapResult<Vec<Workspace>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn query_info(
+        impl_windows: &[ImplWindow],
+        fields: WindowInfoFields,
+    ) -> XCapResult<Vec<WindowInfo>> {
+        Ok(impl_windows
+            .iter()
+            .map(|impl_window| WindowInfo::fetch(impl_window, fields))
+            .collect())
+    }
+
+    pub fn from_id(_id: u32) -> XCapResult<ImplWindow> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn focused() -> XCapResult<ImplWindow> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn from_pid(_pid: u32) -> XCapResult<Vec<ImplWindow>> {
+        Ok(Vec::new())
+    }
+
+    pub fn from_point(_x: i32, _y: i32) -> XCapResult<ImplWindow> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn id(&self) -> XCapResult<u32> {
         Err(XCapError::NotSupported)
     }
@@ -100,10 +156,26 @@ impl ImplWindow {
         Err(XCapError::NotSupported)
     }
 
+    pub fn pid_source(&self) -> XCapResult<PidSource> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn app_info(&self) -> XCapResult<AppInfo> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn title(&self) -> XCapResult<String> {
         Err(XCapError::NotSupported)
     }
 
+    pub fn title_lossy(&self) -> XCapResult<String> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn icon(&self, _preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn current_monitor(&self) -> XCapResult<ImplMonitor> {
         Err(XCapError::NotSupported)
     }
@@ -128,6 +200,22 @@ impl ImplWindow {
         Err(XCapError::NotSupported)
     }
 
+    pub fn frame_extents(&self) -> XCapResult<FrameExtents> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn shadow_extents(&self) -> XCapResult<FrameExtents> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn outer_rect(&self) -> XCapResult<Rect> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn inner_rect(&self) -> XCapResult<Rect> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         Err(XCapError::NotSupported)
     }
@@ -140,9 +228,70 @@ impl ImplWindow {
         Err(XCapError::NotSupported)
     }
 
+    pub fn workspace(&self) -> XCapResult<Option<u32>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_override_redirect(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_managed(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn parent(&self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn children(&self) -> XCapResult<Vec<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn state(&self) -> XCapResult<WindowState> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         Err(XCapError::NotSupported)
     }
+
+    pub fn capture_image_with(&self, _options: WindowCaptureOptions) -> XCapResult<RgbaImage> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture_region(
+        &self,
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> XCapResult<RgbaImage> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn thumbnail(&self, _max_width: u32, _max_height: u32) -> XCapResult<RgbaImage> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn video_recorder(
+        &self,
+        _options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture(&self, _options: WindowCaptureOptions) -> XCapResult<WindowCapture> {
+        Err(XCapError::NotSupported)
+    }
 }
 
 #[derive(Debug, Clone)]
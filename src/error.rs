@@ -2,6 +2,32 @@ use std::sync::PoisonError;
 
 use thiserror::Error;
 
+/// The category of an [`XCapError`], to decide whether to retry or prompt the user.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ErrorKind {
+    /// The user cancelled the request, e.g. by dismissing the portal dialog,
+    /// or the request ended without an answer.
+    Cancelled,
+    /// The user or the system denied the request, e.g. the portal does not allow it.
+    PermissionDenied,
+    /// The backend is missing or too old, e.g. an old portal version or no display server.
+    Unavailable,
+    /// The request did not complete in time.
+    Timeout,
+    /// The request was aborted through a [`crate::CancelHandle`], or the portal ended it with a failure.
+    Aborted,
+    /// The connection to the display server was lost.
+    DisplayGone,
+    /// The operation is not supported on this platform.
+    NotSupported,
+    /// The capture region is outside the monitor or window bounds.
+    InvalidCaptureRegion,
+    /// The monitor, window or portal stream does not exist (anymore).
+    NotFound,
+    /// Any other error.
+    Other,
+}
+
 #[derive(Debug, Error)]
 pub enum XCapError {
     #[error("Not supported")]
@@ -12,6 +38,44 @@ pub enum XCapError {
     StdSyncPoisonError(String),
     #[error("Invalid capture region: {0}")]
     InvalidCaptureRegion(String),
+    #[error("Cancelled: {message}")]
+    Cancelled {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    #[error("Permission denied: {message}")]
+    PermissionDenied {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    #[error("Unavailable: {message}")]
+    Unavailable {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    #[error("Timeout: {message}")]
+    Timeout {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    #[error("Aborted: {message}")]
+    Aborted {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    #[error("Not found: {message}")]
+    NotFound {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    #[error(transparent)]
+    StdIOError(#[from] std::io::Error),
 
     #[cfg(target_os = "linux")]
     #[error(transparent)]
@@ -30,9 +94,6 @@ pub enum XCapError {
     ZbusError(#[from] zbus::Error),
     #[cfg(target_os = "linux")]
     #[error(transparent)]
-    StdIOError(#[from] std::io::Error),
-    #[cfg(target_os = "linux")]
-    #[error(transparent)]
     StdTimeSystemTimeError(#[from] std::time::SystemTimeError),
     #[cfg(target_os = "linux")]
     #[error(transparent)]
@@ -63,6 +124,73 @@ impl XCapError {
     pub fn new<S: ToString>(err: S) -> Self {
         XCapError::Error(err.to_string())
     }
+
+    pub(crate) fn not_found<S: ToString>(message: S) -> Self {
+        XCapError::NotFound {
+            message: message.to_string(),
+            source: None,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            XCapError::NotSupported => ErrorKind::NotSupported,
+            XCapError::InvalidCaptureRegion(_) => ErrorKind::InvalidCaptureRegion,
+            XCapError::Cancelled { .. } => ErrorKind::Cancelled,
+            XCapError::PermissionDenied { .. } => ErrorKind::PermissionDenied,
+            XCapError::Unavailable { .. } => ErrorKind::Unavailable,
+            XCapError::Timeout { .. } => ErrorKind::Timeout,
+            XCapError::Aborted { .. } => ErrorKind::Aborted,
+            XCapError::NotFound { .. } => ErrorKind::NotFound,
+            #[cfg(target_os = "linux")]
+            XCapError::XcbError(xcb::Error::Connection(_)) | XCapError::XcbConnError(_) => {
+                ErrorKind::DisplayGone
+            }
+            #[cfg(target_os = "linux")]
+            XCapError::ZbusError(err) => zbus_error_kind(err),
+            #[cfg(target_os = "windows")]
+            XCapError::WindowsCoreError(err)
+                if err.code() == windows::Win32::Foundation::E_ACCESSDENIED =>
+            {
+                ErrorKind::PermissionDenied
+            }
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn zbus_error_kind(err: &zbus::Error) -> ErrorKind {
+    use zbus::fdo;
+
+    match err {
+        zbus::Error::FDO(err) => match err.as_ref() {
+            fdo::Error::AccessDenied(_) | fdo::Error::AuthFailed(_) => ErrorKind::PermissionDenied,
+            fdo::Error::ServiceUnknown(_)
+            | fdo::Error::NameHasNoOwner(_)
+            | fdo::Error::UnknownInterface(_)
+            | fdo::Error::UnknownMethod(_) => ErrorKind::Unavailable,
+            fdo::Error::NoReply(_) | fdo::Error::Timeout(_) | fdo::Error::TimedOut(_) => {
+                ErrorKind::Timeout
+            }
+            _ => ErrorKind::Other,
+        },
+        zbus::Error::MethodError(name, _, _) => match name.as_str() {
+            "org.freedesktop.DBus.Error.AccessDenied"
+            | "org.freedesktop.DBus.Error.AuthFailed"
+            | "org.freedesktop.portal.Error.NotAllowed" => ErrorKind::PermissionDenied,
+            "org.freedesktop.portal.Error.NotFound" => ErrorKind::NotFound,
+            "org.freedesktop.DBus.Error.ServiceUnknown"
+            | "org.freedesktop.DBus.Error.NameHasNoOwner"
+            | "org.freedesktop.DBus.Error.UnknownInterface"
+            | "org.freedesktop.DBus.Error.UnknownMethod" => ErrorKind::Unavailable,
+            "org.freedesktop.DBus.Error.NoReply"
+            | "org.freedesktop.DBus.Error.Timeout"
+            | "org.freedesktop.DBus.Error.TimedOut" => ErrorKind::Timeout,
+            _ => ErrorKind::Other,
+        },
+        _ => ErrorKind::Other,
+    }
 }
 
 pub type XCapResult<T> = Result<T, XCapError>;
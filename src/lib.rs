@@ -1,7 +1,9 @@
 mod error;
 mod monitor;
+mod token_store;
 mod video_recorder;
 mod window;
+mod window_query;
 
 #[cfg(target_os = "macos")]
 #[path = "macos/mod.rs"]
@@ -21,10 +23,20 @@ mod platform;
 mod dir;
 
 pub use image;
+#[cfg(feature = "regex")]
+pub use regex;
 
-pub use error::{XCapError, XCapResult};
-pub use monitor::Monitor;
-pub use window::Window;
+pub use error::{ErrorKind, XCapError, XCapResult};
+pub use monitor::{Monitor, cursor_position};
+pub use window::{
+    AppInfo, FrameExtents, PidSource, Rect, Window, WindowCapture, WindowCaptureOptions,
+    WindowListOptions, WindowState, WindowType, Workspace, WorkspaceFilter, workspaces,
+};
+pub use token_store::{FileTokenStore, MemoryTokenStore, TokenKey, TokenStore};
+pub use window_query::WindowQuery;
 
 pub use video_recorder::Frame;
 pub use video_recorder::VideoRecorder;
+pub use video_recorder::{
+    CancelHandle, PersistMode, PipeWireRemote, ScaleMode, VideoRecorderOptions,
+};
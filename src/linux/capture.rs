@@ -1,13 +1,18 @@
 use image::RgbaImage;
 
-use crate::error::XCapResult;
+use crate::{
+    error::{XCapError, XCapResult},
+    window::{FrameExtents, Rect, WindowCapture, WindowCaptureOptions},
+};
 
 use super::{
     impl_monitor::ImplMonitor,
     impl_window::ImplWindow,
-    utils::{get_current_screen_buf, get_monitor_info_buf, wayland_detect},
+    utils::{
+        get_current_screen_buf, get_monitor_info_buf, has_compositing_manager, wayland_detect,
+    },
     wayland_capture::wayland_capture,
-    xorg_capture::xorg_capture,
+    xorg_capture::{xorg_capture, xorg_capture_window_pixmap},
 };
 
 pub fn capture_monitor(impl_monitor: &ImplMonitor) -> XCapResult<RgbaImage> {
@@ -58,8 +63,382 @@ pub fn capture_region(
 }
 
 pub fn capture_window(impl_window: &ImplWindow) -> XCapResult<RgbaImage> {
-    let width = impl_window.width()?;
-    let height = impl_window.height()?;
+    let window_capture = capture_window_with(impl_window, WindowCaptureOptions::default())?;
+
+    Ok(window_capture.image)
+}
+
+/// 截取窗口时用到的 frame 窗口信息，没有 reparenting 窗口管理器时就是客户端窗口本身
+#[derive(Debug, Clone)]
+struct FrameGeometry {
+    window: ImplWindow,
+    rect: Rect,
+    border_width: u32,
+}
+
+/// 窗口的边界形状，bounds 和 rects 均为 root 窗口坐标
+#[derive(Debug, Clone)]
+struct WindowShape {
+    bounds: Rect,
+    rects: Vec<Rect>,
+}
+
+/// 截取窗口区域时需要查询的窗口信息，录制时缓存起来，只有窗口变化时才重新查询
+#[derive(Debug, Clone)]
+pub struct WindowGeometry {
+    inner_rect: Rect,
+    frame: Option<FrameGeometry>,
+    compositing: bool,
+    shapes: Vec<WindowShape>,
+}
+
+impl WindowGeometry {
+    pub fn new(impl_window: &ImplWindow) -> XCapResult<Self> {
+        let inner_rect = impl_window.inner_rect()?;
+
+        let frame = impl_window
+            .frame_window()
+            .and_then(|frame_window| {
+                Ok(FrameGeometry {
+                    rect: frame_window.inner_rect()?,
+                    border_width: frame_window.border_width()?,
+                    window: frame_window,
+                })
+            })
+            .inspect_err(|err| log::debug!("get frame window failed {err}"))
+            .ok();
+
+        // 客户端窗口和窗口管理器的 frame 窗口都可能设置了形状，例如圆角窗口
+        let mut shaped_windows = vec![(impl_window, inner_rect)];
+        if let Some(frame) = &frame
+            && frame.window.window != impl_window.window
+        {
+            shaped_windows.push((&frame.window, frame.rect));
+        }
+
+        let mut shapes = Vec::new();
+        for (shaped_window, window_rect) in shaped_windows {
+            match window_shape(shaped_window, window_rect) {
+                Ok(Some(shape)) => shapes.push(shape),
+                Ok(None) => {}
+                Err(err) => log::debug!("get window shape failed {err}"),
+            }
+        }
+
+        Ok(WindowGeometry {
+            inner_rect,
+            frame,
+            compositing: has_compositing_manager().unwrap_or(false),
+            shapes,
+        })
+    }
+
+    pub fn inner_rect(&self) -> Rect {
+        self.inner_rect
+    }
+}
+
+/// 从顶层 frame 窗口的合成 pixmap 中截取区域，x、y 为 root 窗口坐标
+fn capture_window_pixmap(
+    frame: &FrameGeometry,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> XCapResult<RgbaImage> {
+    let border_width = frame.border_width as i32;
+
+    // pixmap 的原点在边框的左上角
+    let pixmap_x = x - frame.rect.x + border_width;
+    let pixmap_y = y - frame.rect.y + border_width;
+    let pixmap_width = frame.rect.width as i32 + border_width * 2;
+    let pixmap_height = frame.rect.height as i32 + border_width * 2;
+
+    if pixmap_x < 0
+        || pixmap_y < 0
+        || pixmap_x + width as i32 > pixmap_width
+        || pixmap_y + height as i32 > pixmap_height
+    {
+        return Err(XCapError::new("Capture area is outside the window pixmap"));
+    }
+
+    xorg_capture_window_pixmap(frame.window.window, pixmap_x, pixmap_y, width, height)
+}
+
+pub fn capture_window_with(
+    impl_window: &ImplWindow,
+    options: WindowCaptureOptions,
+) -> XCapResult<WindowCapture> {
+    let inner_rect = impl_window.inner_rect()?;
+    let frame_extents = if options.include_frame {
+        impl_window.frame_extents()?
+    } else {
+        FrameExtents::default()
+    };
+    let shadow_extents = if options.exclude_shadow {
+        impl_window.shadow_extents()?
+    } else {
+        FrameExtents::default()
+    };
+
+    // 截图区域相对于客户端窗口的坐标，frame 向外扩展，阴影向内收缩
+    let left = shadow_extents.left as i32 - frame_extents.left as i32;
+    let top = shadow_extents.top as i32 - frame_extents.top as i32;
+    let width = (inner_rect.width + frame_extents.left + frame_extents.right) as i32
+        - (shadow_extents.left + shadow_extents.right) as i32;
+    let height = (inner_rect.height + frame_extents.top + frame_extents.bottom) as i32
+        - (shadow_extents.top + shadow_extents.bottom) as i32;
+
+    if width <= 0 || height <= 0 {
+        return Err(XCapError::new("Window capture area is empty"));
+    }
+
+    capture_window_area(impl_window, left, top, width as u32, height as u32)
+}
+
+/// 截取相对于客户端窗口坐标的区域，区域可以超出客户端窗口，例如包含窗口管理器绘制的边框
+pub fn capture_window_area(
+    impl_window: &ImplWindow,
+    left: i32,
+    top: i32,
+    width: u32,
+    height: u32,
+) -> XCapResult<WindowCapture> {
+    let geometry = WindowGeometry::new(impl_window)?;
+
+    capture_window_area_with(impl_window, &geometry, left, top, width, height)
+}
+
+/// 使用已经查询过的窗口信息截取区域，参见 [`capture_window_area`]
+pub fn capture_window_area_with(
+    impl_window: &ImplWindow,
+    geometry: &WindowGeometry,
+    left: i32,
+    top: i32,
+    width: u32,
+    height: u32,
+) -> XCapResult<WindowCapture> {
+    let x = geometry.inner_rect.x + left;
+    let y = geometry.inner_rect.y + top;
+
+    // 有合成管理器时，窗口内容保存在离屏 pixmap 中，不会被其他窗口覆盖
+    let pixmap_image = match &geometry.frame {
+        Some(frame) if geometry.compositing => capture_window_pixmap(frame, x, y, width, height)
+            .inspect_err(|err| log::debug!("capture window pixmap failed {err}"))
+            .ok(),
+        _ => None,
+    };
+
+    let occlusion_safe = pixmap_image.is_some();
+    let mut image = match pixmap_image {
+        Some(image) => image,
+        None => capture_window_drawable(impl_window, geometry, left, top, width, height)?,
+    };
 
-    xorg_capture(impl_window.window, 0, 0, width, height)
+    for shape in &geometry.shapes {
+        mask_outside_shape(&mut image, x, y, &shape.bounds, &shape.rects);
+    }
+
+    Ok(WindowCapture {
+        image,
+        occlusion_safe,
+    })
+}
+
+/// rect 与图像相交的部分，返回图像坐标 (left, top, right, bottom)
+fn image_span(
+    rect: &Rect,
+    image_x: i32,
+    image_y: i32,
+    width: u32,
+    height: u32,
+) -> Option<(u32, u32, u32, u32)> {
+    let clamp = |value: i64, max: u32| value.clamp(0, max as i64) as u32;
+
+    let left = clamp(rect.x as i64 - image_x as i64, width);
+    let top = clamp(rect.y as i64 - image_y as i64, height);
+    let right = clamp(rect.x as i64 + rect.width as i64 - image_x as i64, width);
+    let bottom = clamp(rect.y as i64 + rect.height as i64 - image_y as i64, height);
+
+    (left < right && top < bottom).then_some((left, top, right, bottom))
+}
+
+/// 将窗口范围 bounds 内、形状区域 shape_rects 以外的像素设置为透明，坐标均为 root 窗口坐标
+fn mask_outside_shape(
+    image: &mut RgbaImage,
+    image_x: i32,
+    image_y: i32,
+    bounds: &Rect,
+    shape_rects: &[Rect],
+) {
+    let (width, height) = image.dimensions();
+
+    // 先按行填充每个形状矩形覆盖的像素，避免每个像素都检查所有矩形
+    let mut covered = vec![false; width as usize * height as usize];
+    for shape_rect in shape_rects {
+        let Some((left, top, right, bottom)) =
+            image_span(shape_rect, image_x, image_y, width, height)
+        else {
+            continue;
+        };
+
+        for y in top..bottom {
+            let row = y as usize * width as usize;
+            covered[row + left as usize..row + right as usize].fill(true);
+        }
+    }
+
+    let Some((left, top, right, bottom)) = image_span(bounds, image_x, image_y, width, height)
+    else {
+        return;
+    };
+
+    for y in top..bottom {
+        let row = y as usize * width as usize;
+        for x in left..right {
+            if !covered[row + x as usize] {
+                image.get_pixel_mut(x, y).0 = [0, 0, 0, 0];
+            }
+        }
+    }
+}
+
+fn window_shape(impl_window: &ImplWindow, window_rect: Rect) -> XCapResult<Option<WindowShape>> {
+    let Some(shape_rects) = impl_window.bounding_shape()? else {
+        return Ok(None);
+    };
+
+    let border_width = impl_window.border_width()?;
+
+    // 边界形状包含边框，坐标相对于窗口原点 (边框内侧的左上角)
+    let bounds = Rect {
+        x: window_rect.x - border_width as i32,
+        y: window_rect.y - border_width as i32,
+        width: window_rect.width + border_width * 2,
+        height: window_rect.height + border_width * 2,
+    };
+    let rects = shape_rects
+        .iter()
+        .map(|shape_rect| Rect {
+            x: window_rect.x + shape_rect.x,
+            y: window_rect.y + shape_rect.y,
+            ..*shape_rect
+        })
+        .collect();
+
+    Ok(Some(WindowShape { bounds, rects }))
+}
+
+/// GetImage 直接读取窗口区域，返回的是屏幕上该位置的内容，会包含遮挡在上面的窗口
+fn capture_window_drawable(
+    impl_window: &ImplWindow,
+    geometry: &WindowGeometry,
+    left: i32,
+    top: i32,
+    width: u32,
+    height: u32,
+) -> XCapResult<RgbaImage> {
+    let inner_rect = geometry.inner_rect;
+
+    // 区域在客户端窗口内部，直接截取客户端窗口
+    if left >= 0
+        && top >= 0
+        && left as u32 + width <= inner_rect.width
+        && top as u32 + height <= inner_rect.height
+    {
+        return xorg_capture(impl_window.window, left, top, width, height);
+    }
+
+    let x = inner_rect.x + left;
+    let y = inner_rect.y + top;
+
+    // 区域包含窗口管理器绘制的边框，从 frame 窗口中截取
+    if let Some(frame) = &geometry.frame
+        && frame.window.window != impl_window.window
+    {
+        let frame_rect = frame.rect;
+
+        if x >= frame_rect.x
+            && y >= frame_rect.y
+            && x + width as i32 <= frame_rect.x + frame_rect.width as i32
+            && y + height as i32 <= frame_rect.y + frame_rect.height as i32
+        {
+            return xorg_capture(
+                frame.window.window,
+                x - frame_rect.x,
+                y - frame_rect.y,
+                width,
+                height,
+            );
+        }
+    }
+
+    // 非 reparenting 窗口管理器，只能从 root 窗口截取屏幕上的内容
+    let screen_buf = get_current_screen_buf()?;
+
+    xorg_capture(screen_buf.root(), x, y, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn mask_outside_shape_clears_corners() {
+        let mut image = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        let bounds = Rect {
+            x: 10,
+            y: 10,
+            width: 4,
+            height: 4,
+        };
+        // 去掉四个角的形状
+        let shape_rects = [
+            Rect {
+                x: 11,
+                y: 10,
+                width: 2,
+                height: 4,
+            },
+            Rect {
+                x: 10,
+                y: 11,
+                width: 4,
+                height: 2,
+            },
+        ];
+
+        mask_outside_shape(&mut image, 10, 10, &bounds, &shape_rects);
+
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(3, 3).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(0, 1).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn mask_outside_shape_clips_to_image() {
+        let mut image = RgbaImage::from_pixel(4, 2, Rgba([255, 255, 255, 255]));
+        // 窗口和形状都超出截图范围
+        let bounds = Rect {
+            x: -10,
+            y: -10,
+            width: 100,
+            height: 100,
+        };
+        let shape_rects = [Rect {
+            x: -10,
+            y: -10,
+            width: 12,
+            height: 100,
+        }];
+
+        mask_outside_shape(&mut image, 0, 0, &bounds, &shape_rects);
+
+        assert_eq!(image.get_pixel(1, 1).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(2, 0).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(3, 1).0, [0, 0, 0, 0]);
+    }
 }
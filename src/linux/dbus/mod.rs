@@ -27,7 +27,7 @@ mod tests {
     use crate::platform::dbus::screencast::{CreateSessionOption, CreateSessionResponse, CursorModes, PersistMode, ScreenCastProxyBlocking, SelectSourcesOption, SourceType, StartOption, StartResponse};
     use crate::platform::dbus::session::{session_handle_path, SessionProxyBlocking};
     use crate::platform::dbus::{generate_session_handle, generate_token_handle};
-    use crate::platform::utils::{get_zbus_connection, Connection};
+    use crate::platform::utils::{get_zbus_connection, Connection, PortalRequestOptions};
     use zbus::zvariant::{ObjectPath, OwnedObjectPath};
 
     fn create_session<'a>(
@@ -39,7 +39,7 @@ mod tests {
         let session_handle_path = session_handle_path(conn, &session_token)?;
 
         let resp: Responses<CreateSessionResponse> =
-            on_blocking_response(conn, handle_token.as_str(), || {
+            on_blocking_response(conn, handle_token.as_str(), &PortalRequestOptions::default(), || {
                 proxy.create_session(CreateSessionOption {
                     handle_token: &handle_token,
                     session_handle_token: &session_token,
@@ -72,7 +72,7 @@ mod tests {
         mut opt: SelectSourcesOption,
     ) -> anyhow::Result<()> {
         let handle_token = generate_token_handle();
-        let resp: Responses<()> = on_blocking_response(conn, handle_token.as_str(), || {
+        let resp: Responses<()> = on_blocking_response(conn, handle_token.as_str(), &PortalRequestOptions::default(), || {
             opt.handle_token = &handle_token;
             proxy.select_sources(session.as_ref(), opt)?;
             Ok(())
@@ -116,7 +116,7 @@ mod tests {
 
         let handle_token = generate_token_handle();
         println!("start handle token: {}", handle_token);
-        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), || {
+        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), &PortalRequestOptions::default(), || {
             proxy.start(session_path.as_ref(), "", StartOption {
                 handle_token: &handle_token,
             })?;
@@ -143,7 +143,7 @@ mod tests {
         })?;
 
         let handle_token = generate_token_handle();
-        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), || {
+        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), &PortalRequestOptions::default(), || {
             proxy.start(session_path.as_ref(), "", StartOption {
                 handle_token: &handle_token,
             })?;
@@ -172,7 +172,7 @@ mod tests {
         })?;
 
         let handle_token = generate_token_handle();
-        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), || {
+        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), &PortalRequestOptions::default(), || {
             proxy.start(session_path.as_ref(), "", StartOption {
                 handle_token: &handle_token,
             })?;
@@ -202,7 +202,7 @@ mod tests {
         })?;
 
         // It should not display dialog
-        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), || {
+        let resp: Responses<StartResponse> = on_blocking_response(conn, handle_token.as_str(), &PortalRequestOptions::default(), || {
             proxy.start(session_path.as_ref(), "", StartOption {
                 handle_token: &handle_token,
             })?;
@@ -1,4 +1,6 @@
-use crate::platform::utils::Connection;
+use crate::platform::utils::{
+    Connection, PortalRequestOptions, portal_response_error, wait_portal_response,
+};
 use crate::{XCapError, XCapResult};
 use const_format::concatcp;
 use log::trace;
@@ -50,6 +52,11 @@ impl ResponseCode {
     pub fn is_success(&self) -> bool {
         *self == ResponseCode::Success
     }
+
+    /// The error of a request the portal did not complete, cancelled by the user or ended otherwise
+    pub fn to_error(self) -> XCapError {
+        portal_response_error(self as u32)
+    }
 }
 
 #[derive(serde::Deserialize, zvariant::Type, Debug)]
@@ -134,23 +141,24 @@ pub fn request_handle_path(
     Ok(zvariant::OwnedObjectPath::try_from(fmt)?)
 }
 
+/// Runs `f` and waits for the response of the request it made, closing the request when `options`
+/// time out or cancel it.
 pub fn on_blocking_response<T: FromResponse, F>(
     conn: &Connection,
     token: &str,
+    options: &PortalRequestOptions,
     f: F,
 ) -> XCapResult<Responses<T>>
 where
     F: FnOnce() -> XCapResult<()>,
 {
     let request_handle_path = request_handle_path(conn, token)?;
-    let proxy = RequestProxyBlocking::new(conn, request_handle_path)?;
-    let mut resp_it = proxy.receive_response()?;
+    let proxy = zbus::block_on(RequestProxy::new(conn.inner(), request_handle_path))?;
+    let mut responses = zbus::block_on(proxy.receive_response())?;
 
     f()?;
 
-    let resp = resp_it
-        .next()
-        .ok_or_else(|| XCapError::new("No response received"))?;
+    let resp = wait_portal_response(&proxy, &mut responses, options)?;
 
     let mut resp = resp.args()?;
 
@@ -1,6 +1,7 @@
 use crate::platform::dbus::request::{FromResponse, ResponseMap};
 use crate::{XCapError, XCapResult};
 use bitflags::bitflags;
+use std::collections::HashMap;
 use std::ops::BitAnd;
 use zbus::zvariant;
 
@@ -169,7 +170,9 @@ impl From<SourceType> for zvariant::Value<'static> {
     }
 }
 
-#[derive(Debug, zvariant::Type, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, zvariant::Type, serde::Deserialize, serde::Serialize,
+)]
 #[repr(u32)]
 pub enum PersistMode {
     None,
@@ -218,6 +221,12 @@ pub trait ScreenCast {
         options: StartOption<'_>,
     ) -> zbus::Result<zvariant::OwnedObjectPath>;
 
+    fn open_pipe_wire_remote(
+        &self,
+        session_handle: zvariant::ObjectPath<'_>,
+        options: HashMap<&str, zvariant::Value<'_>>,
+    ) -> zbus::Result<zvariant::OwnedFd>;
+
     #[zbus(property)]
     fn available_cursor_modes(&self) -> zbus::Result<CursorModes>;
 
@@ -0,0 +1,402 @@
+use std::{
+    collections::HashSet,
+    env::{self, var_os},
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use directories::BaseDirs;
+
+// https://specifications.freedesktop.org/desktop-entry-spec/latest/
+#[derive(Debug, Clone, Default)]
+pub struct DesktopEntry {
+    /// Desktop file id, e.g. `org.gnome.Nautilus.desktop`
+    pub id: String,
+    pub name: Option<String>,
+    pub icon: Option<String>,
+    pub exec: Option<String>,
+    pub startup_wm_class: Option<String>,
+}
+
+impl DesktopEntry {
+    fn parse(id: String, content: &str, locales: &[String]) -> Option<DesktopEntry> {
+        let mut entry = DesktopEntry {
+            id,
+            ..Default::default()
+        };
+        // 本地化名称及其在 locales 中的优先级
+        let mut localized_name: Option<(usize, String)> = None;
+
+        let mut in_desktop_entry = false;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                in_desktop_entry = line == "[Desktop Entry]";
+                continue;
+            }
+
+            if !in_desktop_entry {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().to_string();
+
+            match key.trim() {
+                "Name" => entry.name = Some(value),
+                "Icon" => entry.icon = Some(value),
+                "Exec" => entry.exec = Some(value),
+                "StartupWMClass" => entry.startup_wm_class = Some(value),
+                key if key.starts_with("Name[") => {
+                    let locale = key.trim_start_matches("Name[").trim_end_matches(']');
+                    let Some(priority) = locales.iter().position(|item| item == locale) else {
+                        continue;
+                    };
+
+                    if localized_name
+                        .as_ref()
+                        .is_none_or(|(best_priority, _)| priority < *best_priority)
+                    {
+                        localized_name = Some((priority, value));
+                    }
+                }
+                "Type" if value != "Application" => return None,
+                // Hidden 表示该条目已被删除，NoDisplay 的条目仍然可以用于识别应用
+                "Hidden" if value == "true" => return None,
+                _ => {}
+            }
+        }
+
+        if let Some((_, name)) = localized_name {
+            entry.name = Some(name);
+        }
+
+        Some(entry)
+    }
+
+    /// The desktop file id without the `.desktop` suffix
+    pub fn app_id(&self) -> &str {
+        self.id.strip_suffix(".desktop").unwrap_or(&self.id)
+    }
+
+    /// The program of the `Exec` key, skipping a leading `env VAR=value` prefix
+    pub fn exec_program(&self) -> Option<&str> {
+        self.exec
+            .as_deref()?
+            .split_whitespace()
+            .map(|arg| arg.trim_matches('"'))
+            .find(|arg| *arg != "env" && !arg.contains('='))
+    }
+}
+
+/// Locale keys to look up in order, e.g. `zh_CN.UTF-8` => [`zh_CN`, `zh`]
+fn current_locales() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(var_os)
+        .map(|value| value.to_string_lossy().to_string())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+
+    // 去掉编码和修饰符，例如 zh_CN.UTF-8@xxx => zh_CN
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut locales = vec![locale.to_string()];
+    if let Some((lang, _)) = locale.split_once('_') {
+        locales.push(lang.to_string());
+    }
+
+    locales
+}
+
+/// $XDG_DATA_HOME 和 $XDG_DATA_DIRS，优先级从高到低
+fn xdg_data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(base_dirs) = BaseDirs::new() {
+        dirs.push(base_dirs.data_dir().to_path_buf());
+    }
+
+    let data_dirs = var_os("XDG_DATA_DIRS")
+        .filter(|data_dirs| !data_dirs.is_empty())
+        .unwrap_or("/usr/local/share:/usr/share".into());
+    dirs.extend(env::split_paths(&data_dirs));
+
+    dirs
+}
+
+fn collect_desktop_entries(
+    base: &Path,
+    dir: &Path,
+    locales: &[String],
+    visited: &mut HashSet<PathBuf>,
+    entries: &mut Vec<DesktopEntry>,
+) {
+    // 目录可能是符号链接，记录访问过的真实路径，避免符号链接成环时无限递归
+    let Ok(canonical_dir) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canonical_dir) {
+        return;
+    }
+
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for dir_entry in read_dir.flatten() {
+        let path = dir_entry.path();
+        if path.is_dir() {
+            collect_desktop_entries(base, &path, locales, visited, entries);
+            continue;
+        }
+
+        if path
+            .extension()
+            .is_none_or(|extension| extension != "desktop")
+        {
+            continue;
+        }
+
+        // 子目录中的文件 id 使用 `-` 连接，例如 kde4/konsole.desktop => kde4-konsole.desktop
+        let Ok(relative_path) = path.strip_prefix(base) else {
+            continue;
+        };
+        let id = relative_path.to_string_lossy().replace('/', "-");
+
+        // 高优先级目录中的同名文件会覆盖低优先级目录中的文件
+        if entries.iter().any(|entry| entry.id == id) {
+            continue;
+        }
+
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        if let Some(entry) = DesktopEntry::parse(id, &content, locales) {
+            entries.push(entry);
+        }
+    }
+}
+
+static DESKTOP_ENTRIES: OnceLock<Vec<DesktopEntry>> = OnceLock::new();
+
+/// All desktop entries of the XDG data dirs, parsed once and cached for the lifetime of the process
+pub fn all_desktop_entries() -> &'static [DesktopEntry] {
+    DESKTOP_ENTRIES.get_or_init(|| {
+        let mut entries = Vec::new();
+        let mut visited = HashSet::new();
+        let locales = current_locales();
+
+        for data_dir in xdg_data_dirs() {
+            let applications_dir = data_dir.join("applications");
+            collect_desktop_entries(
+                &applications_dir,
+                &applications_dir,
+                &locales,
+                &mut visited,
+                &mut entries,
+            );
+        }
+
+        entries
+    })
+}
+
+/// Find the desktop entry of a `WM_CLASS` class name or an application id
+pub fn find_desktop_entry(entries: &[DesktopEntry], class_name: &str) -> Option<DesktopEntry> {
+    if class_name.is_empty() {
+        return None;
+    }
+
+    // StartupWMClass 是最准确的匹配方式
+    let entry = entries
+        .iter()
+        .find(|entry| {
+            entry
+                .startup_wm_class
+                .as_ref()
+                .is_some_and(|startup_wm_class| startup_wm_class == class_name)
+        })
+        // 其次是文件 id，例如 firefox.desktop、org.gnome.Nautilus.desktop
+        .or_else(|| {
+            entries
+                .iter()
+                .find(|entry| entry.app_id().eq_ignore_ascii_case(class_name))
+        })
+        // 最后是反向域名的最后一段，例如 Nautilus => org.gnome.Nautilus.desktop
+        .or_else(|| {
+            entries.iter().find(|entry| {
+                entry
+                    .app_id()
+                    .rsplit('.')
+                    .next()
+                    .is_some_and(|last| last.eq_ignore_ascii_case(class_name))
+            })
+        });
+
+    entry.cloned()
+}
+
+fn icon_size_of_dir(name: &str) -> Option<u32> {
+    // 目录名形如 48x48 或者 48x48@2
+    let (size, _) = name.split_once('x')?;
+    size.parse().ok()
+}
+
+/// Resolve an icon name of a desktop entry to a png file, choosing the size closest to `preferred_size`
+///
+/// Only a subset of the icon theme spec is followed: PNG files in the `<size>x<size>/apps`
+/// directories of every installed theme, then `/usr/share/pixmaps`. Scalable (SVG) icons,
+/// theme inheritance and other directory layouts are not looked up.
+///
+/// https://specifications.freedesktop.org/icon-theme-spec/latest/
+pub fn find_icon_path(icon: &str, preferred_size: u32) -> Option<PathBuf> {
+    let icon_path = Path::new(icon);
+    if icon_path.is_absolute() {
+        return icon_path.is_file().then(|| icon_path.to_path_buf());
+    }
+
+    let filename = format!("{icon}.png");
+    let mut base_dirs = Vec::new();
+    if let Some(home_base_dirs) = BaseDirs::new() {
+        base_dirs.push(home_base_dirs.home_dir().join(".icons"));
+    }
+    base_dirs.extend(xdg_data_dirs().iter().map(|dir| dir.join("icons")));
+
+    let mut best: Option<(u32, PathBuf)> = None;
+
+    for base_dir in base_dirs {
+        let Ok(themes) = fs::read_dir(&base_dir) else {
+            continue;
+        };
+
+        for theme in themes.flatten() {
+            let Ok(size_dirs) = fs::read_dir(theme.path()) else {
+                continue;
+            };
+
+            for size_dir in size_dirs.flatten() {
+                let Some(size) = size_dir.file_name().to_str().and_then(icon_size_of_dir) else {
+                    continue;
+                };
+
+                let path = size_dir.path().join("apps").join(&filename);
+                if !path.is_file() {
+                    continue;
+                }
+
+                let distance = size.abs_diff(preferred_size);
+                if best
+                    .as_ref()
+                    .is_none_or(|(best_distance, _)| distance < *best_distance)
+                {
+                    best = Some((distance, path));
+                }
+            }
+        }
+    }
+
+    if let Some((_, path)) = best {
+        return Some(path);
+    }
+
+    let pixmap_path = Path::new("/usr/share/pixmaps").join(&filename);
+    pixmap_path.is_file().then_some(pixmap_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_desktop_entry() {
+        let content = "[Desktop Entry]\n\
+            Type=Application\n\
+            Name=Files\n\
+            Name[de]=Dateien\n\
+            Icon=org.gnome.Nautilus\n\
+            Exec=nautilus --new-window %U\n\
+            StartupWMClass=Nautilus\n\
+            \n\
+            [Desktop Action new-window]\n\
+            Name=New Window\n";
+
+        let entry =
+            DesktopEntry::parse("org.gnome.Nautilus.desktop".to_string(), content, &[]).unwrap();
+
+        assert_eq!(entry.name.as_deref(), Some("Files"));
+        assert_eq!(entry.exec_program(), Some("nautilus"));
+        assert_eq!(entry.icon.as_deref(), Some("org.gnome.Nautilus"));
+        assert_eq!(entry.startup_wm_class.as_deref(), Some("Nautilus"));
+        assert_eq!(entry.app_id(), "org.gnome.Nautilus");
+
+        let locales = ["de_DE".to_string(), "de".to_string()];
+        let entry =
+            DesktopEntry::parse("org.gnome.Nautilus.desktop".to_string(), content, &locales)
+                .unwrap();
+
+        assert_eq!(entry.name.as_deref(), Some("Dateien"));
+    }
+
+    #[test]
+    fn find_desktop_entry_by_class_name() {
+        let entries = vec![
+            DesktopEntry {
+                id: "org.gnome.Nautilus.desktop".to_string(),
+                ..Default::default()
+            },
+            DesktopEntry {
+                id: "jetbrains-idea.desktop".to_string(),
+                startup_wm_class: Some("jetbrains-idea".to_string()),
+                ..Default::default()
+            },
+            DesktopEntry {
+                id: "code.desktop".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let find_id = |class_name| find_desktop_entry(&entries, class_name).map(|entry| entry.id);
+
+        assert_eq!(
+            find_id("jetbrains-idea").as_deref(),
+            Some("jetbrains-idea.desktop")
+        );
+        assert_eq!(find_id("Code").as_deref(), Some("code.desktop"));
+        assert_eq!(
+            find_id("Nautilus").as_deref(),
+            Some("org.gnome.Nautilus.desktop")
+        );
+        assert_eq!(find_id("unknown"), None);
+    }
+
+    #[test]
+    fn collect_desktop_entries_stops_at_symlink_loops() {
+        let dir = env::temp_dir().join(format!("xcap_desktop_entries_{}", std::process::id()));
+        fs::create_dir_all(dir.join("kde4")).unwrap();
+        fs::write(
+            dir.join("kde4").join("konsole.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Konsole\n",
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("kde4").join("loop")).unwrap();
+
+        let mut entries = Vec::new();
+        collect_desktop_entries(&dir, &dir, &[], &mut HashSet::new(), &mut entries);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, "kde4-konsole.desktop");
+    }
+}
@@ -12,15 +12,16 @@ use xcb::{
 
 use crate::{
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    video_recorder::{Frame, VideoRecorderOptions},
+    window::Rect,
 };
 
 use super::{
     capture::{capture_monitor, capture_region},
-    impl_video_recorder::ImplVideoRecorder,
+    impl_video_recorder::{ImplVideoRecorder, RecorderSource},
     utils::{
-        get_atom, get_current_screen_buf, get_monitor_info_buf, get_xcb_connection_and_index,
-        wayland_detect,
+        get_atom, get_current_screen_buf, get_cursor_position, get_monitor_info_buf,
+        get_xcb_connection_and_index, wayland_detect,
     },
 };
 
@@ -256,7 +257,22 @@ impl ImplMonitor {
             }
         }
 
-        Err(XCapError::new("Not found monitor"))
+        Err(XCapError::not_found("Not found monitor"))
+    }
+
+    pub fn cursor_position() -> XCapResult<(i32, i32)> {
+        // Wayland 没有获取全局鼠标位置的协议，XWayland 只能看到位于 X 窗口上的鼠标
+        if wayland_detect() {
+            return Err(XCapError::NotSupported);
+        }
+
+        let (x, y) = get_cursor_position()?;
+        let scale_factor = get_scale_factor().unwrap_or(1.0);
+
+        Ok((
+            ((x as f32) / scale_factor) as i32,
+            ((y as f32) / scale_factor) as i32,
+        ))
     }
 }
 
@@ -305,6 +321,18 @@ impl ImplMonitor {
         Ok(((height as f32) / scale_factor) as u32)
     }
 
+    /// 窗口的坐标没有按缩放比例换算，比较时需要使用显示器的原始坐标
+    pub fn window_space_rect(&self) -> XCapResult<Rect> {
+        let monitor_info = get_monitor_info_buf(self.output)?;
+
+        Ok(Rect {
+            x: monitor_info.x() as i32,
+            y: monitor_info.y() as i32,
+            width: monitor_info.width() as u32,
+            height: monitor_info.height() as u32,
+        })
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
         let mode_infos = get_mode_infos()?;
         let (rotation, _) = get_rotation_frequency(mode_infos, &self.output).unwrap_or((0.0, 0.0));
@@ -365,7 +393,16 @@ impl ImplMonitor {
         capture_region(self, x, y, width, height)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
-        ImplVideoRecorder::new(self.clone())
+    pub fn video_recorder(
+        &self,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        ImplVideoRecorder::new(RecorderSource::Monitor(self.clone()), options)
+    }
+
+    pub fn video_recorders(
+        options: VideoRecorderOptions,
+    ) -> XCapResult<Vec<(ImplMonitor, ImplVideoRecorder, Receiver<Frame>)>> {
+        ImplVideoRecorder::new_monitors(options)
     }
 }
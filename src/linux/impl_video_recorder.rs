@@ -1,12 +1,22 @@
 use std::sync::mpsc::Receiver;
 
-use crate::{XCapResult, video_recorder::Frame};
+use crate::{
+    XCapResult,
+    video_recorder::{Frame, VideoRecorderOptions},
+};
 
 use super::{
-    impl_monitor::ImplMonitor, utils::wayland_detect, wayland_video_recorder::WaylandVideoRecorder,
-    xorg_video_recorder::XorgVideoRecorder,
+    impl_monitor::ImplMonitor, impl_window::ImplWindow, utils::wayland_detect,
+    wayland_video_recorder::WaylandVideoRecorder, xorg_video_recorder::XorgVideoRecorder,
 };
 
+/// What a recorder captures
+#[derive(Debug, Clone)]
+pub enum RecorderSource {
+    Monitor(ImplMonitor),
+    Window(ImplWindow),
+}
+
 #[derive(Debug, Clone)]
 pub enum ImplVideoRecorder {
     Xorg(XorgVideoRecorder),
@@ -14,16 +24,47 @@ pub enum ImplVideoRecorder {
 }
 
 impl ImplVideoRecorder {
-    pub fn new(monitor: ImplMonitor) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        source: RecorderSource,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         if wayland_detect() {
-            let (recorder, receiver) = WaylandVideoRecorder::new(monitor)?;
+            let (recorder, receiver) = WaylandVideoRecorder::new(source, options)?;
             Ok((ImplVideoRecorder::Wayland(recorder), receiver))
         } else {
-            let (recorder, receiver) = XorgVideoRecorder::new(monitor)?;
+            let (recorder, receiver) = XorgVideoRecorder::new(source, options)?;
             Ok((ImplVideoRecorder::Xorg(recorder), receiver))
         }
     }
 
+    /// One recorder per monitor, sharing a single portal session on Wayland
+    pub fn new_monitors(
+        options: VideoRecorderOptions,
+    ) -> XCapResult<Vec<(ImplMonitor, Self, Receiver<Frame>)>> {
+        if wayland_detect() {
+            let recorders = WaylandVideoRecorder::new_monitors(options)?
+                .into_iter()
+                .map(|(impl_monitor, recorder, receiver)| {
+                    (impl_monitor, ImplVideoRecorder::Wayland(recorder), receiver)
+                })
+                .collect();
+
+            return Ok(recorders);
+        }
+
+        ImplMonitor::all()?
+            .into_iter()
+            .map(|impl_monitor| {
+                let (recorder, receiver) = XorgVideoRecorder::new(
+                    RecorderSource::Monitor(impl_monitor.clone()),
+                    options.clone(),
+                )?;
+
+                Ok((impl_monitor, ImplVideoRecorder::Xorg(recorder), receiver))
+            })
+            .collect()
+    }
+
     pub fn start(&self) -> XCapResult<()> {
         match self {
             ImplVideoRecorder::Xorg(recorder) => recorder.start(),
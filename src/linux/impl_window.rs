@@ -1,19 +1,35 @@
+use std::{fs, path::Path, sync::mpsc::Receiver};
+
 use image::RgbaImage;
 use xcb::{
-    Xid,
+    Connection, Xid, XidNew,
+    res::{ClientIdMask, ClientIdSpec, QueryClientIds},
+    shape,
     x::{
-        ATOM_ATOM, ATOM_CARDINAL, ATOM_NONE, ATOM_STRING, ATOM_WM_CLASS, ATOM_WM_NAME, Atom,
-        Drawable, GetGeometry, GetProperty, GetPropertyReply, QueryPointer, TranslateCoordinates,
-        Window,
+        ATOM_ANY, ATOM_ATOM, ATOM_CARDINAL, ATOM_NONE, ATOM_STRING, ATOM_WINDOW, ATOM_WM_CLASS,
+        ATOM_WM_NAME, ATOM_WM_TRANSIENT_FOR, Atom, Drawable, GetGeometry, GetGeometryCookie,
+        GetProperty, GetPropertyCookie, GetPropertyReply, GetWindowAttributes,
+        GetWindowAttributesCookie, MapState, QueryPointer, QueryTree, TranslateCoordinates, Window,
     },
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    error::{XCapError, XCapResult},
+    video_recorder::{Frame, VideoRecorderOptions},
+    window::{
+        AppInfo, FrameExtents, PidSource, Rect, WindowCapture, WindowCaptureOptions,
+        WindowListOptions, WindowState, WindowType, Workspace, WorkspaceFilter, to_thumbnail,
+    },
+    window_query::{WindowInfo, WindowInfoFields},
+};
 
 use super::{
-    capture::capture_window,
+    capture::{capture_window, capture_window_area, capture_window_with},
+    desktop_entry::{all_desktop_entries, find_desktop_entry, find_icon_path},
     impl_monitor::ImplMonitor,
-    utils::{get_atom, get_xcb_connection_and_index},
+    impl_video_recorder::{ImplVideoRecorder, RecorderSource},
+    text_property::{TextEncoding, decode_text},
+    utils::{get_atom, get_current_screen_buf, get_xcb_connection_and_index, has_extension},
 };
 
 #[derive(Debug, Clone)]
@@ -44,7 +60,81 @@ fn get_window_property(
     Ok(window_property_reply)
 }
 
-pub fn get_window_pid(window: &Window) -> XCapResult<u32> {
+/// 读取 ICCCM 文本属性，按属性的实际类型 (STRING、UTF8_STRING、COMPOUND_TEXT) 解码
+fn get_window_text_property(
+    window: Window,
+    property: Atom,
+    r#type: Atom,
+    lossy: bool,
+) -> XCapResult<String> {
+    let reply = get_window_property(window, property, r#type, 0, 1024)?;
+
+    decode_text_property(&reply, lossy)
+}
+
+fn decode_text_property(reply: &GetPropertyReply, lossy: bool) -> XCapResult<String> {
+    if reply.r#type() == ATOM_NONE {
+        return Ok(String::new());
+    }
+
+    let encoding = if reply.r#type() == ATOM_STRING {
+        TextEncoding::String
+    } else if get_atom("UTF8_STRING").is_ok_and(|atom| atom == reply.r#type()) {
+        TextEncoding::Utf8String
+    } else if get_atom("COMPOUND_TEXT").is_ok_and(|atom| atom == reply.r#type()) {
+        TextEncoding::CompoundText
+    } else if lossy {
+        // 未知类型 (例如 C_STRING) 按 UTF-8 尽量解码
+        TextEncoding::Utf8String
+    } else {
+        return Err(XCapError::new(format!(
+            "Unsupported text property type {}",
+            reply.r#type().resource_id()
+        )));
+    };
+
+    // COMPOUND_TEXT 可能使用尚不支持的字符集 (例如 ISO-8859-5、GB2312)，严格解码时返回错误，由调用方选择有损解码
+    decode_text(encoding, reply.value(), lossy).map_err(|err| {
+        if encoding == TextEncoding::CompoundText {
+            XCapError::new(format!(
+                "Failed to decode COMPOUND_TEXT: {err}, use Window::title_lossy to replace the undecodable characters"
+            ))
+        } else {
+            err
+        }
+    })
+}
+
+fn get_window_title(window: Window, lossy: bool) -> XCapResult<String> {
+    // First try _NET_WM_NAME with UTF8_STRING type
+    let net_wm_name_atom = get_atom("_NET_WM_NAME")?;
+    let utf8_string_atom = get_atom("UTF8_STRING")?;
+    let title = get_window_text_property(window, net_wm_name_atom, utf8_string_atom, lossy)?;
+
+    if !title.is_empty() {
+        return Ok(title);
+    }
+
+    // If _NET_WM_NAME is empty, fall back to WM_NAME, which may be STRING, UTF8_STRING or COMPOUND_TEXT
+    let title = get_window_text_property(window, ATOM_WM_NAME, ATOM_ANY, lossy)?;
+
+    // If both are empty, try to get the parent window
+    if title.is_empty() {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let query_tree_cookie = conn.send_request(&QueryTree { window });
+        if let Ok(query_tree_reply) = conn.wait_for_reply(query_tree_cookie) {
+            let parent = query_tree_reply.parent();
+            if parent.resource_id() != 0 {
+                // Try to get title from parent window
+                return get_window_title(parent, lossy);
+            }
+        }
+    }
+
+    Ok(title)
+}
+
+fn get_window_net_wm_pid(window: &Window) -> XCapResult<u32> {
     let wm_pid_atom = get_atom("_NET_WM_PID")?;
 
     let reply = get_window_property(*window, wm_pid_atom, ATOM_CARDINAL, 0, 4)?;
@@ -56,6 +146,423 @@ pub fn get_window_pid(window: &Window) -> XCapResult<u32> {
         .copied()
 }
 
+fn get_window_xres_pid(window: &Window) -> XCapResult<u32> {
+    // https://gitlab.freedesktop.org/xorg/proto/xorgproto/-/blob/master/resproto.txt
+    if !has_extension("X-Resource")? {
+        return Err(XCapError::new("X-Resource extension not supported"));
+    }
+
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let query_client_ids_cookie = conn.send_request(&QueryClientIds {
+        specs: &[ClientIdSpec {
+            client: window.resource_id(),
+            mask: ClientIdMask::LOCAL_CLIENT_PID,
+        }],
+    });
+    let query_client_ids_reply = conn.wait_for_reply(query_client_ids_cookie)?;
+
+    query_client_ids_reply
+        .ids()
+        .find(|id| id.spec().mask.contains(ClientIdMask::LOCAL_CLIENT_PID))
+        .and_then(|id| id.value().first().copied())
+        .ok_or(XCapError::new("Get window pid failed"))
+}
+
+/// 优先使用 _NET_WM_PID，很多客户端（旧版 Java、部分 Electron、远程 X 客户端）不会设置该属性，
+/// 此时通过 X-Resource 扩展从 X server 查询客户端连接对应的进程 id
+pub fn get_window_pid(window: &Window) -> XCapResult<(u32, PidSource)> {
+    match get_window_net_wm_pid(window) {
+        Ok(pid) => Ok((pid, PidSource::NetWmPid)),
+        Err(err) => {
+            log::debug!("get _NET_WM_PID failed {err}");
+            let pid = get_window_xres_pid(window)?;
+
+            Ok((pid, PidSource::XRes))
+        }
+    }
+}
+
+/// _NET_FRAME_EXTENTS 和 _GTK_FRAME_EXTENTS 都是 CARDINAL[4]，依次为 left、right、top、bottom
+fn get_frame_extents(window: &Window, name: &str) -> XCapResult<FrameExtents> {
+    // atom 不存在说明没有任何客户端设置过该属性
+    let Ok(frame_extents_atom) = get_atom(name) else {
+        return Ok(FrameExtents::default());
+    };
+
+    let reply = get_window_property(*window, frame_extents_atom, ATOM_CARDINAL, 0, 4)?;
+    let value = reply.value::<u32>();
+
+    match value {
+        [left, right, top, bottom] => Ok(FrameExtents {
+            left: *left,
+            right: *right,
+            top: *top,
+            bottom: *bottom,
+        }),
+        _ => Ok(FrameExtents::default()),
+    }
+}
+
+/// 窗口管理器会把客户端窗口 reparent 到 frame 窗口中，frame 窗口是 root 窗口的直接子窗口
+fn get_frame_window(window: &Window) -> XCapResult<Window> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+
+    let mut current_window = *window;
+    loop {
+        let query_tree_cookie = conn.send_request(&QueryTree {
+            window: current_window,
+        });
+        let query_tree_reply = conn.wait_for_reply(query_tree_cookie)?;
+
+        let parent = query_tree_reply.parent();
+        if parent.resource_id() == 0 || parent == query_tree_reply.root() {
+            return Ok(current_window);
+        }
+
+        current_window = parent;
+    }
+}
+
+/// 读取 root 窗口上的客户端窗口列表，窗口管理器不支持 EWMH 时返回 None
+fn get_client_list(
+    root_window: Window,
+    client_list_atom: Option<Atom>,
+) -> XCapResult<Option<Vec<Window>>> {
+    let client_list_atom = match client_list_atom {
+        Some(client_list_atom) => client_list_atom,
+        None => return Ok(None),
+    };
+
+    let list_window_reply = get_window_property(root_window, client_list_atom, ATOM_NONE, 0, 1024)?;
+    if list_window_reply.r#type() == ATOM_NONE {
+        return Ok(None);
+    }
+
+    Ok(Some(list_window_reply.value::<Window>().to_vec()))
+}
+
+/// 窗口管理器会在它管理的客户端窗口上设置 WM_STATE
+fn has_wm_state(window: Window) -> XCapResult<bool> {
+    // 没有任何窗口设置过 WM_STATE 时，这个 atom 不存在
+    let wm_state_atom = match get_atom("WM_STATE") {
+        Ok(wm_state_atom) => wm_state_atom,
+        Err(_) => return Ok(false),
+    };
+
+    let wm_state_reply = get_window_property(window, wm_state_atom, ATOM_ANY, 0, 0)?;
+
+    Ok(wm_state_reply.r#type() != ATOM_NONE)
+}
+
+/// 在 frame 窗口中查找设置了 WM_STATE 的客户端窗口
+fn find_client_window(window: Window) -> XCapResult<Option<Window>> {
+    if has_wm_state(window)? {
+        return Ok(Some(window));
+    }
+
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let query_tree_cookie = conn.send_request(&QueryTree { window });
+    let query_tree_reply = conn.wait_for_reply(query_tree_cookie)?;
+
+    for &child in query_tree_reply.children() {
+        if let Some(client_window) = find_client_window(child)? {
+            return Ok(Some(client_window));
+        }
+    }
+
+    Ok(None)
+}
+
+/// 通过 QueryTree 按堆叠顺序 (从下到上) 列出 root 窗口的子窗口，frame 窗口替换为其中的客户端窗口，
+/// 可见的 override-redirect 窗口 (include_override_redirect 为 true 时) 和未被管理的顶层窗口原样返回。
+/// client_list 为 None 表示窗口管理器不支持 EWMH，此时设置了 WM_STATE 的窗口都视为被管理的窗口
+fn get_top_level_windows(
+    root_window: Window,
+    client_list: Option<&[Window]>,
+    include_override_redirect: bool,
+) -> XCapResult<Vec<Window>> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+
+    let query_tree_cookie = conn.send_request(&QueryTree {
+        window: root_window,
+    });
+    let query_tree_reply = conn.wait_for_reply(query_tree_cookie)?;
+    let children = query_tree_reply.children();
+
+    // 先发送所有请求再等待回复，避免每个窗口都要往返一次
+    let get_window_attributes_cookies: Vec<_> = children
+        .iter()
+        .map(|&window| conn.send_request(&GetWindowAttributes { window }))
+        .collect();
+
+    let mut windows = Vec::new();
+
+    for (&child, get_window_attributes_cookie) in children.iter().zip(get_window_attributes_cookies)
+    {
+        // 窗口可能在枚举过程中被销毁
+        let get_window_attributes_reply = match conn.wait_for_reply(get_window_attributes_cookie) {
+            Ok(get_window_attributes_reply) => get_window_attributes_reply,
+            _ => continue,
+        };
+        let is_viewable = get_window_attributes_reply.map_state() == MapState::Viewable;
+
+        // 菜单、提示框等 override-redirect 窗口
+        if get_window_attributes_reply.override_redirect() {
+            if is_viewable && include_override_redirect {
+                windows.push(child);
+            }
+            continue;
+        }
+
+        let client_window = find_client_window(child).ok().flatten();
+        let managed_window = match (client_window, client_list) {
+            (Some(client_window), Some(client_list)) => client_list
+                .contains(&client_window)
+                .then_some(client_window),
+            (client_window, None) => client_window,
+            (None, _) => None,
+        };
+
+        // 被管理的窗口最小化后 frame 窗口不可见，但仍然需要列出
+        match managed_window {
+            Some(managed_window) => windows.push(managed_window),
+            None if is_viewable => windows.push(child),
+            None => {}
+        }
+    }
+
+    Ok(windows)
+}
+
+/// _NET_WM_DESKTOP 为 0xFFFFFFFF 表示窗口显示在所有桌面上
+const ALL_DESKTOPS: u32 = 0xFFFFFFFF;
+
+fn get_window_desktop(window: &Window) -> XCapResult<Option<u32>> {
+    // 窗口管理器不支持 EWMH 时，这个 atom 不存在
+    let wm_desktop_atom = match get_atom("_NET_WM_DESKTOP") {
+        Ok(wm_desktop_atom) => wm_desktop_atom,
+        Err(_) => return Ok(None),
+    };
+
+    let wm_desktop_reply = get_window_property(*window, wm_desktop_atom, ATOM_CARDINAL, 0, 1)?;
+
+    Ok(wm_desktop_reply.value::<u32>().first().copied())
+}
+
+fn get_root_cardinals(root_window: Window, name: &str, len: u32) -> XCapResult<Vec<u32>> {
+    let atom = get_atom(name)?;
+    let reply = get_window_property(root_window, atom, ATOM_CARDINAL, 0, len)?;
+
+    Ok(reply.value::<u32>().to_vec())
+}
+
+fn get_current_desktop(root_window: Window) -> XCapResult<u32> {
+    get_root_cardinals(root_window, "_NET_CURRENT_DESKTOP", 1)?
+        .first()
+        .copied()
+        .ok_or(XCapError::new("Get current desktop failed"))
+}
+
+/// 没有 _NET_WM_DESKTOP 的窗口 (override-redirect 窗口等) 不属于任何工作区，视为在当前工作区可见，
+/// 窗口管理器不支持工作区 (没有 _NET_CURRENT_DESKTOP) 时不过滤
+fn filter_workspace(
+    root_window: Window,
+    windows: Vec<Window>,
+    workspace_filter: WorkspaceFilter,
+) -> XCapResult<Vec<Window>> {
+    let current_desktop = match workspace_filter {
+        WorkspaceFilter::All => return Ok(windows),
+        WorkspaceFilter::Current => match get_current_desktop(root_window) {
+            Ok(current_desktop) => Some(current_desktop),
+            Err(_) => return Ok(windows),
+        },
+        WorkspaceFilter::Sticky => None,
+    };
+
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let net_wm_desktop_atom = get_atom("_NET_WM_DESKTOP").ok();
+    let net_wm_state_atom = get_atom("_NET_WM_STATE").ok();
+    let sticky_atom = get_atom("_NET_WM_STATE_STICKY").ok();
+
+    // 先发送所有请求再等待回复，避免每个窗口都要往返一次
+    let cookies: Vec<_> = windows
+        .iter()
+        .map(|&window| {
+            (
+                net_wm_desktop_atom
+                    .map(|atom| send_get_property(conn, window, atom, ATOM_CARDINAL, 1)),
+                net_wm_state_atom.map(|atom| send_get_property(conn, window, atom, ATOM_ATOM, 32)),
+            )
+        })
+        .collect();
+
+    let mut filtered_windows = Vec::with_capacity(windows.len());
+
+    for (window, (desktop_cookie, state_cookie)) in windows.into_iter().zip(cookies) {
+        // 窗口可能在枚举过程中被销毁
+        let desktop = match desktop_cookie.map(|cookie| conn.wait_for_reply(cookie)) {
+            Some(Ok(reply)) => reply.value::<u32>().first().copied(),
+            Some(Err(_)) => continue,
+            None => None,
+        };
+        let is_sticky_state = state_cookie
+            .and_then(|cookie| conn.wait_for_reply(cookie).ok())
+            .is_some_and(|reply| {
+                sticky_atom.is_some_and(|sticky_atom| reply.value::<Atom>().contains(&sticky_atom))
+            });
+        let is_sticky = desktop == Some(ALL_DESKTOPS) || is_sticky_state;
+
+        let keep = match (current_desktop, desktop) {
+            (Some(current_desktop), Some(desktop)) => desktop == current_desktop || is_sticky,
+            (Some(_), None) => true,
+            (None, _) => is_sticky,
+        };
+
+        if keep {
+            filtered_windows.push(window);
+        }
+    }
+
+    Ok(filtered_windows)
+}
+
+fn app_name_from_wm_class(wm_class: &str) -> String {
+    // WM_CLASS contains two strings: instance name and class name
+    // We want the class name (second string)
+    wm_class
+        .split('\u{0}')
+        .nth(1) // Take the second string (class name)
+        .unwrap_or("")
+        .to_string()
+}
+
+/// 批量查询窗口信息时为每个窗口发送的请求，只发送查询需要的请求
+struct WindowInfoCookies {
+    wm_class: Option<GetPropertyCookie>,
+    wm_name: Option<GetPropertyCookie>,
+    net_wm_name: Option<GetPropertyCookie>,
+    net_wm_pid: Option<GetPropertyCookie>,
+    net_wm_state: Option<GetPropertyCookie>,
+    net_wm_window_type: Option<GetPropertyCookie>,
+    wm_transient_for: Option<GetPropertyCookie>,
+    net_wm_desktop: Option<GetPropertyCookie>,
+    get_window_attributes: Option<GetWindowAttributesCookie>,
+    get_geometry: Option<GetGeometryCookie>,
+}
+
+/// _NET_WM_NAME 和 WM_NAME 中第一个不为空的标题
+fn non_empty_title(net_wm_name: Option<String>, wm_name: Option<String>) -> Option<String> {
+    [net_wm_name, wm_name]
+        .into_iter()
+        .flatten()
+        .find(|title| !title.is_empty())
+}
+
+/// 标题属性都为空的窗口从父窗口读取标题，与 get_window_title 相同，但每一层都批量查询
+fn get_parent_titles(
+    conn: &Connection,
+    windows: &[Window],
+    net_wm_name_atom: Option<Atom>,
+    utf8_string_atom: Atom,
+) -> Vec<String> {
+    let mut titles = vec![String::new(); windows.len()];
+    let mut pending: Vec<(usize, Window)> = windows.iter().copied().enumerate().collect();
+
+    while !pending.is_empty() {
+        let query_tree_cookies: Vec<_> = pending
+            .iter()
+            .map(|&(_, window)| conn.send_request(&QueryTree { window }))
+            .collect();
+        let parents: Vec<(usize, Window)> = pending
+            .iter()
+            .zip(query_tree_cookies)
+            .filter_map(|(&(index, _), query_tree_cookie)| {
+                let parent = conn.wait_for_reply(query_tree_cookie).ok()?.parent();
+
+                (parent.resource_id() != 0).then_some((index, parent))
+            })
+            .collect();
+
+        let title_cookies: Vec<_> = parents
+            .iter()
+            .map(|&(_, parent)| {
+                (
+                    net_wm_name_atom
+                        .map(|atom| send_get_property(conn, parent, atom, utf8_string_atom, 1024)),
+                    send_get_property(conn, parent, ATOM_WM_NAME, ATOM_ANY, 1024),
+                )
+            })
+            .collect();
+
+        pending = Vec::new();
+        for ((index, parent), (net_wm_name_cookie, wm_name_cookie)) in
+            parents.into_iter().zip(title_cookies)
+        {
+            let net_wm_name = net_wm_name_cookie
+                .and_then(|cookie| conn.wait_for_reply(cookie).ok())
+                .and_then(|reply| decode_text_property(&reply, false).ok());
+            let wm_name = conn
+                .wait_for_reply(wm_name_cookie)
+                .ok()
+                .and_then(|reply| decode_text_property(&reply, false).ok());
+
+            match non_empty_title(net_wm_name, wm_name) {
+                Some(title) => titles[index] = title,
+                None => pending.push((index, parent)),
+            }
+        }
+    }
+
+    titles
+}
+
+/// 没有 _NET_WM_PID 的窗口通过 X-Resource 扩展批量查询进程 id
+fn get_xres_pids(conn: &Connection, windows: &[Window]) -> Vec<Option<u32>> {
+    if !has_extension("X-Resource").unwrap_or(false) {
+        return vec![None; windows.len()];
+    }
+
+    let query_client_ids_cookies: Vec<_> = windows
+        .iter()
+        .map(|window| {
+            conn.send_request(&QueryClientIds {
+                specs: &[ClientIdSpec {
+                    client: window.resource_id(),
+                    mask: ClientIdMask::LOCAL_CLIENT_PID,
+                }],
+            })
+        })
+        .collect();
+
+    query_client_ids_cookies
+        .into_iter()
+        .map(|query_client_ids_cookie| {
+            conn.wait_for_reply(query_client_ids_cookie)
+                .ok()?
+                .ids()
+                .find(|id| id.spec().mask.contains(ClientIdMask::LOCAL_CLIENT_PID))
+                .and_then(|id| id.value().first().copied())
+        })
+        .collect()
+}
+
+fn send_get_property(
+    conn: &Connection,
+    window: Window,
+    property: Atom,
+    r#type: Atom,
+    long_length: u32,
+) -> GetPropertyCookie {
+    conn.send_request(&GetProperty {
+        delete: false,
+        window,
+        property,
+        r#type,
+        long_offset: 0,
+        long_length,
+    })
+}
+
 fn get_active_window_id() -> XCapResult<u32> {
     let (conn, _) = get_xcb_connection_and_index()?;
     let active_window_atom = get_atom("_NET_ACTIVE_WINDOW")?;
@@ -73,6 +580,86 @@ fn get_active_window_id() -> XCapResult<u32> {
     Err(XCapError::new("Get active window id failed"))
 }
 
+fn is_window_viewable(window: &Window) -> XCapResult<bool> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let get_window_attributes_cookie = conn.send_request(&GetWindowAttributes { window: *window });
+    let get_window_attributes_reply = conn.wait_for_reply(get_window_attributes_cookie)?;
+
+    Ok(get_window_attributes_reply.map_state() == MapState::Viewable)
+}
+
+fn get_window_icon(window: &Window, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+    // https://specifications.freedesktop.org/wm-spec/1.5/ar01s05.html#id-1.6.13
+    let wm_icon_atom = get_atom("_NET_WM_ICON")?;
+    let wm_icon_reply = get_window_property(*window, wm_icon_atom, ATOM_CARDINAL, 0, u32::MAX / 4)?;
+
+    Ok(decode_net_wm_icon(
+        wm_icon_reply.value::<u32>(),
+        preferred_size,
+    ))
+}
+
+/// _NET_WM_ICON 由多个 width, height, width * height 个 ARGB 像素组成，选择尺寸最接近的一个
+fn decode_net_wm_icon(data: &[u32], preferred_size: u32) -> Option<RgbaImage> {
+    let mut best: Option<(u32, u32, &[u32])> = None;
+
+    let mut rest = data;
+    while let [width, height, tail @ ..] = rest {
+        let (width, height) = (*width, *height);
+        let len = width as usize * height as usize;
+        if len == 0 || len > tail.len() {
+            break;
+        }
+        let (pixels, tail) = tail.split_at(len);
+        rest = tail;
+
+        let distance = width.max(height).abs_diff(preferred_size);
+        // 距离相同时优先选择更大的图标，缩小比放大效果更好
+        let is_better = best.is_none_or(|(best_width, best_height, _)| {
+            let best_distance = best_width.max(best_height).abs_diff(preferred_size);
+            distance < best_distance || (distance == best_distance && width > best_width)
+        });
+
+        if is_better {
+            best = Some((width, height, pixels));
+        }
+    }
+
+    let (width, height, pixels) = best?;
+    let rgba = pixels
+        .iter()
+        .flat_map(|pixel| {
+            let [a, r, g, b] = pixel.to_be_bytes();
+            [r, g, b, a]
+        })
+        .collect();
+
+    RgbaImage::from_raw(width, height, rgba)
+}
+
+fn get_gtk_application_id(window: &Window) -> XCapResult<String> {
+    let gtk_application_id_atom = get_atom("_GTK_APPLICATION_ID")?;
+    let utf8_string_atom = get_atom("UTF8_STRING")?;
+    let gtk_application_id_reply =
+        get_window_property(*window, gtk_application_id_atom, utf8_string_atom, 0, 1024)?;
+
+    Ok(String::from_utf8(
+        gtk_application_id_reply.value().to_vec(),
+    )?)
+}
+
+fn get_process_command_line(pid: u32) -> XCapResult<Vec<String>> {
+    let cmdline = fs::read(format!("/proc/{pid}/cmdline"))?;
+
+    let command_line = cmdline
+        .split(|&byte| byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).to_string())
+        .collect();
+
+    Ok(command_line)
+}
+
 fn get_position_and_size(window: &Window) -> XCapResult<(i32, i32, u32, u32)> {
     let (conn, _) = get_xcb_connection_and_index()?;
     let get_geometry_cookie = conn.send_request(&GetGeometry {
@@ -96,26 +683,96 @@ fn get_position_and_size(window: &Window) -> XCapResult<(i32, i32, u32, u32)> {
     ))
 }
 
-fn get_window_state(window: &Window) -> XCapResult<(bool, bool)> {
-    // https://specifications.freedesktop.org/wm-spec/1.3/ar01s05.html
+fn get_window_state(window: &Window) -> XCapResult<WindowState> {
     let wm_state_atom = get_atom("_NET_WM_STATE")?;
-    let wm_state_hidden_atom = get_atom("_NET_WM_STATE_HIDDEN")?;
-    let wm_state_maximized_vert_atom = get_atom("_NET_WM_STATE_MAXIMIZED_VERT")?;
-    let wm_state_maximized_horz_atom = get_atom("_NET_WM_STATE_MAXIMIZED_HORZ")?;
+    let wm_state_reply = get_window_property(*window, wm_state_atom, ATOM_ATOM, 0, 32)?;
+
+    Ok(window_state_from_atoms(wm_state_reply.value::<Atom>()))
+}
 
-    let wm_state_reply = get_window_property(*window, wm_state_atom, ATOM_ATOM, 0, 12)?;
-    let wm_state = wm_state_reply.value::<Atom>();
+fn window_state_from_atoms(wm_state: &[Atom]) -> WindowState {
+    // https://specifications.freedesktop.org/wm-spec/1.5/ar01s05.html#id-1.6.8
+    const WM_STATES: [(&str, WindowState); 12] = [
+        ("_NET_WM_STATE_HIDDEN", WindowState::Hidden),
+        ("_NET_WM_STATE_MAXIMIZED_HORZ", WindowState::MaximizedHorz),
+        ("_NET_WM_STATE_MAXIMIZED_VERT", WindowState::MaximizedVert),
+        ("_NET_WM_STATE_FULLSCREEN", WindowState::Fullscreen),
+        ("_NET_WM_STATE_ABOVE", WindowState::Above),
+        ("_NET_WM_STATE_BELOW", WindowState::Below),
+        ("_NET_WM_STATE_STICKY", WindowState::Sticky),
+        ("_NET_WM_STATE_SHADED", WindowState::Shaded),
+        ("_NET_WM_STATE_SKIP_TASKBAR", WindowState::SkipTaskbar),
+        ("_NET_WM_STATE_SKIP_PAGER", WindowState::SkipPager),
+        ("_NET_WM_STATE_MODAL", WindowState::Modal),
+        (
+            "_NET_WM_STATE_DEMANDS_ATTENTION",
+            WindowState::DemandsAttention,
+        ),
+    ];
+
+    let mut window_state = WindowState::empty();
+    for (name, flag) in WM_STATES {
+        // 未注册的 atom 不可能出现在窗口状态中
+        if let Ok(atom) = get_atom(name)
+            && wm_state.contains(&atom)
+        {
+            window_state |= flag;
+        }
+    }
 
-    let is_minimized = wm_state.contains(&wm_state_hidden_atom);
+    window_state
+}
 
-    let is_maximized_vert = wm_state.contains(&wm_state_maximized_vert_atom);
+fn get_window_type(window: &Window) -> XCapResult<WindowType> {
+    let wm_window_type_atom = get_atom("_NET_WM_WINDOW_TYPE")?;
+    let wm_window_type_reply = get_window_property(*window, wm_window_type_atom, ATOM_ATOM, 0, 32)?;
 
-    let is_maximized_horz = wm_state.contains(&wm_state_maximized_horz_atom);
+    if let Some(window_type) = window_type_from_atoms(wm_window_type_reply.value::<Atom>()) {
+        return Ok(window_type);
+    }
 
-    Ok((
-        is_minimized,
-        !is_minimized && is_maximized_vert && is_maximized_horz,
-    ))
+    // 没有设置类型时，设置了 WM_TRANSIENT_FOR 的窗口视为对话框，其余视为普通窗口
+    let transient_for_reply =
+        get_window_property(*window, ATOM_WM_TRANSIENT_FOR, ATOM_WINDOW, 0, 1)?;
+    if transient_for_reply.value::<Window>().is_empty() {
+        Ok(WindowType::Normal)
+    } else {
+        Ok(WindowType::Dialog)
+    }
+}
+
+fn window_type_from_atoms(wm_window_types: &[Atom]) -> Option<WindowType> {
+    // https://specifications.freedesktop.org/wm-spec/1.5/ar01s05.html#id-1.6.7
+    const WM_WINDOW_TYPES: [(&str, WindowType); 14] = [
+        ("_NET_WM_WINDOW_TYPE_NORMAL", WindowType::Normal),
+        ("_NET_WM_WINDOW_TYPE_DESKTOP", WindowType::Desktop),
+        ("_NET_WM_WINDOW_TYPE_DOCK", WindowType::Dock),
+        ("_NET_WM_WINDOW_TYPE_TOOLBAR", WindowType::Toolbar),
+        ("_NET_WM_WINDOW_TYPE_MENU", WindowType::Menu),
+        ("_NET_WM_WINDOW_TYPE_UTILITY", WindowType::Utility),
+        ("_NET_WM_WINDOW_TYPE_SPLASH", WindowType::Splash),
+        ("_NET_WM_WINDOW_TYPE_DIALOG", WindowType::Dialog),
+        (
+            "_NET_WM_WINDOW_TYPE_DROPDOWN_MENU",
+            WindowType::DropdownMenu,
+        ),
+        ("_NET_WM_WINDOW_TYPE_POPUP_MENU", WindowType::PopupMenu),
+        ("_NET_WM_WINDOW_TYPE_TOOLTIP", WindowType::Tooltip),
+        ("_NET_WM_WINDOW_TYPE_NOTIFICATION", WindowType::Notification),
+        ("_NET_WM_WINDOW_TYPE_COMBO", WindowType::Combo),
+        ("_NET_WM_WINDOW_TYPE_DND", WindowType::Dnd),
+    ];
+
+    // 属性值按照优先级排序，取第一个能识别的类型
+    for wm_window_type in wm_window_types {
+        for (name, window_type) in WM_WINDOW_TYPES {
+            if get_atom(name).is_ok_and(|atom| atom == *wm_window_type) {
+                return Some(window_type);
+            }
+        }
+    }
+
+    None
 }
 
 impl ImplWindow {
@@ -124,6 +781,10 @@ impl ImplWindow {
     }
 
     pub fn all() -> XCapResult<Vec<ImplWindow>> {
+        ImplWindow::all_with(WindowListOptions::default())
+    }
+
+    pub fn all_with(options: WindowListOptions) -> XCapResult<Vec<ImplWindow>> {
         let (conn, _) = get_xcb_connection_and_index()?;
 
         let setup = conn.get_setup();
@@ -131,7 +792,8 @@ impl ImplWindow {
         // https://github.com/rust-x-bindings/rust-xcb/blob/main/examples/get_all_windows.rs
         // https://specifications.freedesktop.org/wm-spec/1.5/ar01s03.html#id-1.4.4
         // list all windows by stacking order
-        let client_list_atom = get_atom("_NET_CLIENT_LIST_STACKING")?;
+        // 不支持 EWMH 的窗口管理器不会创建这个 atom，此时通过 QueryTree 枚举窗口
+        let client_list_atom = get_atom("_NET_CLIENT_LIST_STACKING").ok();
 
         let mut impl_windows = Vec::new();
 
@@ -147,13 +809,27 @@ impl ImplWindow {
             };
 
             if query_pointer_reply.same_screen() {
-                let list_window_reply =
-                    match get_window_property(root_window, client_list_atom, ATOM_NONE, 0, 1024) {
-                        Ok(list_window_reply) => list_window_reply,
+                let client_list = match get_client_list(root_window, client_list_atom) {
+                    Ok(client_list) => client_list,
+                    _ => continue,
+                };
+
+                // 没有 EWMH 时也需要通过 QueryTree 枚举，但只在 include_unmanaged 时包含 override-redirect 窗口
+                let windows = match client_list {
+                    Some(client_list) if !options.include_unmanaged => client_list,
+                    client_list => match get_top_level_windows(
+                        root_window,
+                        client_list.as_deref(),
+                        options.include_unmanaged,
+                    ) {
+                        Ok(windows) => windows,
                         _ => continue,
-                    };
+                    },
+                };
 
-                for &window in list_window_reply.value::<Window>() {
+                let windows = filter_workspace(root_window, windows, options.workspace)?;
+
+                for window in windows {
                     impl_windows.push(ImplWindow::new(window));
                 }
             }
@@ -164,6 +840,325 @@ impl ImplWindow {
 
         Ok(impl_windows)
     }
+
+    pub fn workspaces() -> XCapResult<Vec<Workspace>> {
+        let screen_buf = get_current_screen_buf()?;
+        let root_window = screen_buf.root();
+
+        let number_of_desktops = get_root_cardinals(root_window, "_NET_NUMBER_OF_DESKTOPS", 1)?
+            .first()
+            .copied()
+            .ok_or(XCapError::new("Get number of desktops failed"))?;
+        let current_desktop = get_current_desktop(root_window).ok();
+
+        // _NET_DESKTOP_NAMES 是以 \0 分隔的 UTF8_STRING 列表，数量可能少于桌面数量
+        let names = match get_atom("_NET_DESKTOP_NAMES") {
+            Ok(desktop_names_atom) => {
+                let desktop_names_reply =
+                    get_window_property(root_window, desktop_names_atom, ATOM_ANY, 0, 1024)?;
+                String::from_utf8_lossy(desktop_names_reply.value::<u8>())
+                    .split('\u{0}')
+                    .map(String::from)
+                    .collect()
+            }
+            Err(_) => Vec::new(),
+        };
+
+        // 所有桌面大小相同，_NET_DESKTOP_VIEWPORT 为每个桌面左上角的坐标
+        let geometry =
+            get_root_cardinals(root_window, "_NET_DESKTOP_GEOMETRY", 2).unwrap_or_default();
+        let (width, height) = match geometry[..] {
+            [width, height] => (width, height),
+            _ => (
+                screen_buf.width_in_pixels() as u32,
+                screen_buf.height_in_pixels() as u32,
+            ),
+        };
+        let viewports =
+            get_root_cardinals(root_window, "_NET_DESKTOP_VIEWPORT", number_of_desktops * 2)
+                .unwrap_or_default();
+
+        let workspaces = (0..number_of_desktops)
+            .map(|id| {
+                let index = id as usize;
+                let name = names.get(index).cloned().unwrap_or_default();
+                let (x, y) = match viewports.get(index * 2..index * 2 + 2) {
+                    Some(&[x, y]) => (x as i32, y as i32),
+                    _ => (0, 0),
+                };
+
+                Workspace {
+                    id,
+                    name,
+                    is_current: current_desktop == Some(id),
+                    rect: Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                    },
+                }
+            })
+            .collect();
+
+        Ok(workspaces)
+    }
+
+    /// 先为所有窗口发送请求再统一等待回复，避免每个窗口的每个属性都要往返一次，
+    /// 只查询 fields 中需要的信息
+    pub fn query_info(
+        impl_windows: &[ImplWindow],
+        fields: WindowInfoFields,
+    ) -> XCapResult<Vec<WindowInfo>> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+
+        // 未注册的 atom 不可能设置在窗口上，不需要查询
+        let get_field_atom = |field: WindowInfoFields, name: &str| {
+            fields
+                .contains(field)
+                .then(|| get_atom(name).ok())
+                .flatten()
+        };
+        let net_wm_name_atom = get_field_atom(WindowInfoFields::Title, "_NET_WM_NAME");
+        let utf8_string_atom = get_atom("UTF8_STRING").unwrap_or(ATOM_ANY);
+        let net_wm_pid_atom = get_field_atom(WindowInfoFields::Pid, "_NET_WM_PID");
+        let net_wm_state_atom = get_field_atom(WindowInfoFields::State, "_NET_WM_STATE");
+        let net_wm_window_type_atom =
+            get_field_atom(WindowInfoFields::WindowType, "_NET_WM_WINDOW_TYPE");
+        let net_wm_desktop_atom = get_field_atom(WindowInfoFields::Workspace, "_NET_WM_DESKTOP");
+
+        let window_info_cookies: Vec<WindowInfoCookies> = impl_windows
+            .iter()
+            .map(|impl_window| {
+                let window = impl_window.window;
+                let send_optional = |property: Option<Atom>, r#type: Atom, long_length: u32| {
+                    property.map(|property| {
+                        send_get_property(conn, window, property, r#type, long_length)
+                    })
+                };
+                let send_field = |field: WindowInfoFields, property: Atom, r#type: Atom| {
+                    send_optional(fields.contains(field).then_some(property), r#type, 1024)
+                };
+
+                WindowInfoCookies {
+                    wm_class: send_field(WindowInfoFields::AppName, ATOM_WM_CLASS, ATOM_STRING),
+                    wm_name: send_field(WindowInfoFields::Title, ATOM_WM_NAME, ATOM_ANY),
+                    net_wm_name: send_optional(net_wm_name_atom, utf8_string_atom, 1024),
+                    net_wm_pid: send_optional(net_wm_pid_atom, ATOM_CARDINAL, 4),
+                    net_wm_state: send_optional(net_wm_state_atom, ATOM_ATOM, 32),
+                    net_wm_window_type: send_optional(net_wm_window_type_atom, ATOM_ATOM, 32),
+                    // 没有设置类型时需要根据 WM_TRANSIENT_FOR 判断
+                    wm_transient_for: send_field(
+                        WindowInfoFields::WindowType,
+                        ATOM_WM_TRANSIENT_FOR,
+                        ATOM_WINDOW,
+                    ),
+                    net_wm_desktop: send_optional(net_wm_desktop_atom, ATOM_CARDINAL, 1),
+                    get_window_attributes: fields
+                        .contains(WindowInfoFields::State)
+                        .then(|| conn.send_request(&GetWindowAttributes { window })),
+                    get_geometry: fields.contains(WindowInfoFields::Rect).then(|| {
+                        conn.send_request(&GetGeometry {
+                            drawable: Drawable::Window(window),
+                        })
+                    }),
+                }
+            })
+            .collect();
+
+        let mut window_infos = Vec::with_capacity(impl_windows.len());
+        let mut geometries = Vec::with_capacity(impl_windows.len());
+        // 标题和进程 id 的回退查询较少，在所有回复都收到之后再批量查询
+        let mut untitled_windows = Vec::new();
+        let mut pidless_windows = Vec::new();
+
+        for (index, (impl_window, cookies)) in
+            impl_windows.iter().zip(window_info_cookies).enumerate()
+        {
+            let window = impl_window.window;
+            let wait_optional = |cookie: Option<GetPropertyCookie>| {
+                cookie.and_then(|cookie| conn.wait_for_reply(cookie).ok())
+            };
+
+            let app_name = wait_optional(cookies.wm_class)
+                .and_then(|reply| decode_text_property(&reply, false).ok())
+                .map(|wm_class| app_name_from_wm_class(&wm_class));
+
+            let net_wm_name = wait_optional(cookies.net_wm_name)
+                .and_then(|reply| decode_text_property(&reply, false).ok());
+            let wm_name = wait_optional(cookies.wm_name);
+            // 窗口已经被销毁时 WM_NAME 也读取失败，不需要从父窗口读取
+            let has_wm_name = wm_name.is_some();
+            let wm_name = wm_name.and_then(|reply| decode_text_property(&reply, false).ok());
+            let title = non_empty_title(net_wm_name, wm_name);
+            if fields.contains(WindowInfoFields::Title) && title.is_none() && has_wm_name {
+                untitled_windows.push((index, window));
+            }
+
+            let pid = wait_optional(cookies.net_wm_pid)
+                .and_then(|reply| reply.value::<u32>().first().copied());
+            if fields.contains(WindowInfoFields::Pid) && pid.is_none() {
+                pidless_windows.push((index, window));
+            }
+
+            let window_state = wait_optional(cookies.net_wm_state)
+                .map(|reply| window_state_from_atoms(reply.value::<Atom>()));
+            let is_minimized = fields.contains(WindowInfoFields::State).then(|| {
+                window_state.is_some_and(|window_state| window_state.contains(WindowState::Hidden))
+            });
+            let is_visible = cookies
+                .get_window_attributes
+                .and_then(|cookie| conn.wait_for_reply(cookie).ok())
+                .map(|reply| reply.map_state() == MapState::Viewable && is_minimized != Some(true));
+
+            let window_type_from_type = wait_optional(cookies.net_wm_window_type)
+                .and_then(|reply| window_type_from_atoms(reply.value::<Atom>()));
+            let window_type_from_transient_for =
+                wait_optional(cookies.wm_transient_for).map(|reply| {
+                    if reply.value::<Window>().is_empty() {
+                        WindowType::Normal
+                    } else {
+                        WindowType::Dialog
+                    }
+                });
+            let window_type = window_type_from_type.or(window_type_from_transient_for);
+
+            let workspace = match cookies.net_wm_desktop {
+                Some(cookie) => conn
+                    .wait_for_reply(cookie)
+                    .ok()
+                    .map(|reply| reply.value::<u32>().first().copied()),
+                None => fields.contains(WindowInfoFields::Workspace).then_some(None),
+            }
+            .map(|desktop| desktop.filter(|&desktop| desktop != ALL_DESKTOPS));
+
+            geometries.push(
+                cookies
+                    .get_geometry
+                    .and_then(|cookie| conn.wait_for_reply(cookie).ok()),
+            );
+            window_infos.push(WindowInfo {
+                app_name,
+                title,
+                pid,
+                rect: None,
+                is_minimized,
+                is_visible,
+                window_type,
+                workspace,
+            });
+        }
+
+        let (indices, windows): (Vec<_>, Vec<_>) = untitled_windows.into_iter().unzip();
+        let titles = get_parent_titles(conn, &windows, net_wm_name_atom, utf8_string_atom);
+        for (index, title) in indices.into_iter().zip(titles) {
+            window_infos[index].title = Some(title);
+        }
+
+        let (indices, windows): (Vec<_>, Vec<_>) = pidless_windows.into_iter().unzip();
+        let pids = get_xres_pids(conn, &windows);
+        for (index, pid) in indices.into_iter().zip(pids) {
+            window_infos[index].pid = pid;
+        }
+
+        // 窗口坐标需要根据 GetGeometry 的结果转换到 root 窗口坐标系
+        let translate_coordinates_cookies: Vec<_> = impl_windows
+            .iter()
+            .zip(&geometries)
+            .map(|(impl_window, get_geometry_reply)| {
+                get_geometry_reply.as_ref().map(|get_geometry_reply| {
+                    conn.send_request(&TranslateCoordinates {
+                        dst_window: get_geometry_reply.root(),
+                        src_window: impl_window.window,
+                        src_x: get_geometry_reply.x(),
+                        src_y: get_geometry_reply.y(),
+                    })
+                })
+            })
+            .collect();
+
+        for ((window_info, get_geometry_reply), translate_coordinates_cookie) in window_infos
+            .iter_mut()
+            .zip(geometries)
+            .zip(translate_coordinates_cookies)
+        {
+            if let (Some(get_geometry_reply), Some(translate_coordinates_cookie)) =
+                (get_geometry_reply, translate_coordinates_cookie)
+                && let Ok(translate_coordinates_reply) =
+                    conn.wait_for_reply(translate_coordinates_cookie)
+            {
+                window_info.rect = Some(Rect {
+                    x: (translate_coordinates_reply.dst_x() - get_geometry_reply.x()) as i32,
+                    y: (translate_coordinates_reply.dst_y() - get_geometry_reply.y()) as i32,
+                    width: get_geometry_reply.width() as u32,
+                    height: get_geometry_reply.height() as u32,
+                });
+            }
+        }
+
+        Ok(window_infos)
+    }
+
+    pub fn from_id(id: u32) -> XCapResult<ImplWindow> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let window = Window::new(id);
+
+        // 通过查询窗口属性确认窗口存在
+        let get_window_attributes_cookie = conn.send_request(&GetWindowAttributes { window });
+        match conn.wait_for_reply(get_window_attributes_cookie) {
+            Ok(_) => Ok(ImplWindow::new(window)),
+            Err(xcb::Error::Protocol(err)) => Err(XCapError::NotFound {
+                message: format!("Not found window {id}"),
+                source: Some(Box::new(err)),
+            }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn focused() -> XCapResult<ImplWindow> {
+        let active_window_id = get_active_window_id()?;
+
+        if active_window_id == 0 {
+            return Err(XCapError::not_found("Not found focused window"));
+        }
+
+        Ok(ImplWindow::new(Window::new(active_window_id)))
+    }
+
+    pub fn from_pid(pid: u32) -> XCapResult<Vec<ImplWindow>> {
+        let impl_windows = ImplWindow::all()?
+            .into_iter()
+            .filter(|impl_window| {
+                get_window_pid(&impl_window.window).is_ok_and(|(window_pid, _)| window_pid == pid)
+            })
+            .collect();
+
+        Ok(impl_windows)
+    }
+
+    pub fn from_point(x: i32, y: i32) -> XCapResult<ImplWindow> {
+        // all 返回的窗口已经按照 z 轴从上到下排序，第一个命中的就是最上层的窗口
+        for impl_window in ImplWindow::all()? {
+            if !is_window_viewable(&impl_window.window).unwrap_or(false) {
+                continue;
+            }
+
+            if impl_window.is_minimized().unwrap_or(false) {
+                continue;
+            }
+
+            let (left, top, width, height) = match get_position_and_size(&impl_window.window) {
+                Ok(position_and_size) => position_and_size,
+                _ => continue,
+            };
+
+            if x >= left && x < left + width as i32 && y >= top && y < top + height as i32 {
+                return Ok(impl_window);
+            }
+        }
+
+        Err(XCapError::not_found("Not found window"))
+    }
 }
 
 impl ImplWindow {
@@ -172,61 +1167,85 @@ impl ImplWindow {
     }
 
     pub fn pid(&self) -> XCapResult<u32> {
-        get_window_pid(&self.window)
+        let (pid, _) = get_window_pid(&self.window)?;
+
+        Ok(pid)
+    }
+
+    pub fn pid_source(&self) -> XCapResult<PidSource> {
+        let (_, pid_source) = get_window_pid(&self.window)?;
+
+        Ok(pid_source)
     }
 
     pub fn app_name(&self) -> XCapResult<String> {
-        let get_class_reply =
-            get_window_property(self.window, ATOM_WM_CLASS, ATOM_STRING, 0, 1024)?;
+        let wm_class = get_window_text_property(self.window, ATOM_WM_CLASS, ATOM_STRING, false)?;
 
-        let wm_class = String::from_utf8(get_class_reply.value().to_vec())?;
+        Ok(app_name_from_wm_class(&wm_class))
+    }
 
-        // WM_CLASS contains two strings: instance name and class name
-        // We want the class name (second string)
-        let app_name = wm_class
-            .split('\u{0}')
-            .nth(1) // Take the second string (class name)
-            .unwrap_or("")
-            .to_string();
+    pub fn app_info(&self) -> XCapResult<AppInfo> {
+        let pid = self.pid().ok();
+        let executable = pid.and_then(|pid| fs::read_link(format!("/proc/{pid}/exe")).ok());
+        let command_line = pid
+            .and_then(|pid| get_process_command_line(pid).ok())
+            .unwrap_or_default();
+
+        let wm_class = get_window_text_property(self.window, ATOM_WM_CLASS, ATOM_STRING, true)?;
+        let mut wm_class = wm_class.split('\u{0}');
+        let instance_name = wm_class.next().unwrap_or_default();
+        let class_name = wm_class.next().unwrap_or_default();
+
+        let entries = all_desktop_entries();
+
+        // 依次通过 _GTK_APPLICATION_ID、WM_CLASS 的类名和实例名查找 .desktop 文件
+        let desktop_entry = get_gtk_application_id(&self.window)
+            .ok()
+            .and_then(|gtk_application_id| find_desktop_entry(entries, &gtk_application_id))
+            .or_else(|| find_desktop_entry(entries, class_name))
+            .or_else(|| find_desktop_entry(entries, instance_name))
+            // 最后通过进程的可执行文件匹配 Exec
+            .or_else(|| {
+                let executable = executable.as_ref()?;
+                let executable_name = executable.file_name()?;
+
+                entries
+                    .iter()
+                    .find(|entry| {
+                        entry.exec_program().is_some_and(|program| {
+                            let program = Path::new(program);
+                            program == executable || program.file_name() == Some(executable_name)
+                        })
+                    })
+                    .cloned()
+            });
 
-        Ok(app_name)
+        let name = desktop_entry
+            .as_ref()
+            .and_then(|entry| entry.name.clone())
+            .or_else(|| (!class_name.is_empty()).then(|| class_name.to_string()))
+            .or_else(|| {
+                executable
+                    .as_ref()
+                    .and_then(|executable| executable.file_name())
+                    .map(|file_name| file_name.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+
+        Ok(AppInfo {
+            name,
+            desktop_id: desktop_entry.map(|entry| entry.id),
+            executable,
+            command_line,
+        })
     }
 
     pub fn title(&self) -> XCapResult<String> {
-        // First try _NET_WM_NAME with UTF8_STRING type
-        let net_wm_name_atom = get_atom("_NET_WM_NAME")?;
-        let utf8_string_atom = get_atom("UTF8_STRING")?;
-        let get_title_reply =
-            get_window_property(self.window, net_wm_name_atom, utf8_string_atom, 0, 1024)?;
-        let title = String::from_utf8(get_title_reply.value().to_vec())?;
-
-        // If _NET_WM_NAME is empty, fall back to WM_NAME with COMPOUND_TEXT type
-        if title.is_empty() {
-            let compound_text_atom = get_atom("COMPOUND_TEXT")?;
-            let get_title_reply =
-                get_window_property(self.window, ATOM_WM_NAME, compound_text_atom, 0, 1024)?;
-            let title = String::from_utf8(get_title_reply.value().to_vec())?;
-
-            // If both are empty, try to get the parent window
-            if title.is_empty() {
-                let (conn, _) = get_xcb_connection_and_index()?;
-                let query_tree_cookie = conn.send_request(&xcb::x::QueryTree {
-                    window: self.window,
-                });
-                if let Ok(query_tree_reply) = conn.wait_for_reply(query_tree_cookie) {
-                    let parent = query_tree_reply.parent();
-                    if parent.resource_id() != 0 {
-                        // Try to get title from parent window
-                        let parent_window = ImplWindow::new(parent);
-                        return parent_window.title();
-                    }
-                }
-            }
-
-            return Ok(title);
-        }
+        get_window_title(self.window, false)
+    }
 
-        Ok(title)
+    pub fn title_lossy(&self) -> XCapResult<String> {
+        get_window_title(self.window, true)
     }
 
     pub fn current_monitor(&self) -> XCapResult<ImplMonitor> {
@@ -303,16 +1322,98 @@ impl ImplWindow {
         Ok(height)
     }
 
+    pub fn frame_extents(&self) -> XCapResult<FrameExtents> {
+        get_frame_extents(&self.window, "_NET_FRAME_EXTENTS")
+    }
+
+    pub fn shadow_extents(&self) -> XCapResult<FrameExtents> {
+        get_frame_extents(&self.window, "_GTK_FRAME_EXTENTS")
+    }
+
+    pub fn outer_rect(&self) -> XCapResult<Rect> {
+        let inner_rect = self.inner_rect()?;
+        let frame_extents = self.frame_extents()?;
+
+        Ok(Rect {
+            x: inner_rect.x - frame_extents.left as i32,
+            y: inner_rect.y - frame_extents.top as i32,
+            width: inner_rect.width + frame_extents.left + frame_extents.right,
+            height: inner_rect.height + frame_extents.top + frame_extents.bottom,
+        })
+    }
+
+    pub fn inner_rect(&self) -> XCapResult<Rect> {
+        let (x, y, width, height) = get_position_and_size(&self.window)?;
+
+        Ok(Rect {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    pub(super) fn border_width(&self) -> XCapResult<u32> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let get_geometry_cookie = conn.send_request(&GetGeometry {
+            drawable: Drawable::Window(self.window),
+        });
+        let get_geometry_reply = conn.wait_for_reply(get_geometry_cookie)?;
+
+        Ok(get_geometry_reply.border_width() as u32)
+    }
+
+    /// XShape 扩展设置的边界区域，坐标相对于窗口原点，窗口没有设置形状时返回 None
+    pub(super) fn bounding_shape(&self) -> XCapResult<Option<Vec<Rect>>> {
+        if !has_extension("SHAPE")? {
+            return Ok(None);
+        }
+
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let query_extents_cookie = conn.send_request(&shape::QueryExtents {
+            destination_window: self.window,
+        });
+        let query_extents_reply = conn.wait_for_reply(query_extents_cookie)?;
+        if !query_extents_reply.bounding_shaped() {
+            return Ok(None);
+        }
+
+        let get_rectangles_cookie = conn.send_request(&shape::GetRectangles {
+            window: self.window,
+            source_kind: shape::Sk::Bounding,
+        });
+        let get_rectangles_reply = conn.wait_for_reply(get_rectangles_cookie)?;
+
+        let rects = get_rectangles_reply
+            .rectangles()
+            .iter()
+            .map(|rectangle| Rect {
+                x: rectangle.x as i32,
+                y: rectangle.y as i32,
+                width: rectangle.width as u32,
+                height: rectangle.height as u32,
+            })
+            .collect();
+
+        Ok(Some(rects))
+    }
+
+    pub(super) fn frame_window(&self) -> XCapResult<ImplWindow> {
+        let frame_window = get_frame_window(&self.window)?;
+
+        Ok(ImplWindow::new(frame_window))
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
-        let (is_minimized, _) = get_window_state(&self.window)?;
+        let window_state = get_window_state(&self.window)?;
 
-        Ok(is_minimized)
+        Ok(window_state.contains(WindowState::Hidden))
     }
 
     pub fn is_maximized(&self) -> XCapResult<bool> {
-        let (is_minimized, _) = get_window_state(&self.window)?;
+        let window_state = get_window_state(&self.window)?;
 
-        Ok(is_minimized)
+        Ok(!window_state.contains(WindowState::Hidden) && window_state.is_maximized())
     }
 
     pub fn is_focused(&self) -> XCapResult<bool> {
@@ -321,7 +1422,189 @@ impl ImplWindow {
         Ok(active_window_id == self.id()?)
     }
 
+    pub fn workspace(&self) -> XCapResult<Option<u32>> {
+        let desktop = get_window_desktop(&self.window)?;
+
+        Ok(desktop.filter(|&desktop| desktop != ALL_DESKTOPS))
+    }
+
+    pub fn is_override_redirect(&self) -> XCapResult<bool> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let get_window_attributes_cookie = conn.send_request(&GetWindowAttributes {
+            window: self.window,
+        });
+        let get_window_attributes_reply = conn.wait_for_reply(get_window_attributes_cookie)?;
+
+        Ok(get_window_attributes_reply.override_redirect())
+    }
+
+    pub fn is_managed(&self) -> XCapResult<bool> {
+        has_wm_state(self.window)
+    }
+
+    pub fn parent(&self) -> XCapResult<Option<ImplWindow>> {
+        // 被管理的窗口的父窗口是窗口管理器的 frame 窗口，视为顶层窗口
+        if has_wm_state(self.window)? {
+            return Ok(None);
+        }
+
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let query_tree_cookie = conn.send_request(&QueryTree {
+            window: self.window,
+        });
+        let query_tree_reply = conn.wait_for_reply(query_tree_cookie)?;
+
+        let parent = query_tree_reply.parent();
+        if parent.resource_id() == 0 || parent == query_tree_reply.root() {
+            return Ok(None);
+        }
+
+        Ok(Some(ImplWindow::new(parent)))
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let transient_for_reply =
+            get_window_property(self.window, ATOM_WM_TRANSIENT_FOR, ATOM_WINDOW, 0, 1)?;
+
+        // 窗口组的临时窗口会把 WM_TRANSIENT_FOR 设置为 root 窗口
+        let transient_for = transient_for_reply
+            .value::<Window>()
+            .first()
+            .copied()
+            .filter(|&window| {
+                window.resource_id() != 0
+                    && !conn
+                        .get_setup()
+                        .roots()
+                        .any(|screen| screen.root() == window)
+            });
+
+        Ok(transient_for.map(ImplWindow::new))
+    }
+
+    pub fn children(&self) -> XCapResult<Vec<ImplWindow>> {
+        let (conn, _) = get_xcb_connection_and_index()?;
+        let query_tree_cookie = conn.send_request(&QueryTree {
+            window: self.window,
+        });
+        let query_tree_reply = conn.wait_for_reply(query_tree_cookie)?;
+
+        // QueryTree 按照堆叠顺序从下到上返回子窗口
+        let children = query_tree_reply
+            .children()
+            .iter()
+            .rev()
+            .map(|&window| ImplWindow::new(window))
+            .collect();
+
+        Ok(children)
+    }
+
+    pub fn state(&self) -> XCapResult<WindowState> {
+        get_window_state(&self.window)
+    }
+
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        get_window_type(&self.window)
+    }
+
+    pub fn icon(&self, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        match get_window_icon(&self.window, preferred_size) {
+            Ok(Some(icon)) => return Ok(Some(icon)),
+            Ok(None) => {}
+            Err(err) => log::debug!("get _NET_WM_ICON failed {err}"),
+        }
+
+        // 窗口没有设置 _NET_WM_ICON 时，通过 WM_CLASS 找到对应的 .desktop 文件，再从图标主题中加载图标
+        // 没有 WM_CLASS 的窗口无法找到对应的 .desktop 文件
+        let Some(app_name) = self.app_name().ok().filter(|app_name| !app_name.is_empty()) else {
+            return Ok(None);
+        };
+        let icon_path = find_desktop_entry(all_desktop_entries(), &app_name)
+            .and_then(|entry| entry.icon)
+            .and_then(|icon| find_icon_path(&icon, preferred_size));
+
+        match icon_path {
+            Some(icon_path) => Ok(Some(image::open(icon_path)?.to_rgba8())),
+            None => Ok(None),
+        }
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         capture_window(self)
     }
+
+    pub fn video_recorder(
+        &self,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        ImplVideoRecorder::new(RecorderSource::Window(self.clone()), options)
+    }
+
+    pub fn capture_image_with(&self, options: WindowCaptureOptions) -> XCapResult<RgbaImage> {
+        let window_capture = capture_window_with(self, options)?;
+
+        Ok(window_capture.image)
+    }
+
+    pub fn capture(&self, options: WindowCaptureOptions) -> XCapResult<WindowCapture> {
+        capture_window_with(self, options)
+    }
+
+    pub fn capture_region(&self, x: u32, y: u32, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        // Validate region bounds
+        let window_width = self.width()?;
+        let window_height = self.height()?;
+
+        if width > window_width
+            || height > window_height
+            || x.checked_add(width)
+                .is_none_or(|right| right > window_width)
+            || y.checked_add(height)
+                .is_none_or(|bottom| bottom > window_height)
+        {
+            return Err(XCapError::InvalidCaptureRegion(format!(
+                "Region ({x}, {y}, {width}, {height}) is outside window bounds (0, 0, {window_width}, {window_height})"
+            )));
+        }
+
+        let window_capture = capture_window_area(self, x as i32, y as i32, width, height)?;
+
+        Ok(window_capture.image)
+    }
+
+    pub fn thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        // 不包含边框的客户端区域截图是最快的，有合成管理器时也不会受遮挡影响
+        let image = self.capture_image()?;
+
+        to_thumbnail(&image, max_width, max_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_net_wm_icon_picks_closest_size() {
+        let mut data = vec![2, 2];
+        data.extend([0xFF112233; 4]);
+        data.extend([4, 4]);
+        data.extend([0x80FF0000; 16]);
+
+        let icon = decode_net_wm_icon(&data, 3).unwrap();
+        assert_eq!(icon.dimensions(), (4, 4));
+        assert_eq!(icon.get_pixel(0, 0).0, [0xFF, 0x00, 0x00, 0x80]);
+
+        let icon = decode_net_wm_icon(&data, 1).unwrap();
+        assert_eq!(icon.dimensions(), (2, 2));
+        assert_eq!(icon.get_pixel(1, 1).0, [0x11, 0x22, 0x33, 0xFF]);
+    }
+
+    #[test]
+    fn decode_net_wm_icon_ignores_truncated_data() {
+        assert!(decode_net_wm_icon(&[], 16).is_none());
+        assert!(decode_net_wm_icon(&[16, 16, 0, 0], 16).is_none());
+    }
 }
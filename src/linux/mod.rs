@@ -1,4 +1,6 @@
 mod capture;
+mod desktop_entry;
+mod text_property;
 pub mod utils;
 mod wayland_capture;
 mod wayland_video_recorder;
@@ -0,0 +1,309 @@
+use crate::error::{XCapError, XCapResult};
+
+const ESC: u8 = 0x1b;
+const CSI: u8 = 0x9b;
+const STX: u8 = 0x02;
+
+/// Encodings of ICCCM text properties
+///
+/// https://x.org/releases/X11R7.6/doc/xorg-docs/specs/ICCCM/icccm.html#text_properties
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// `STRING`, ISO-8859-1
+    String,
+    /// `UTF8_STRING`
+    Utf8String,
+    /// `COMPOUND_TEXT`, ISO-2022 based
+    CompoundText,
+}
+
+/// Decode the value of a text property, `lossy` replaces undecodable characters with U+FFFD instead of failing
+pub fn decode_text(encoding: TextEncoding, bytes: &[u8], lossy: bool) -> XCapResult<String> {
+    match encoding {
+        TextEncoding::String => Ok(decode_latin1(bytes)),
+        TextEncoding::Utf8String => decode_utf8(bytes, lossy),
+        TextEncoding::CompoundText => decode_compound_text(bytes, lossy),
+    }
+}
+
+fn decode_latin1(bytes: &[u8]) -> String {
+    // ISO-8859-1 与 Unicode 的前 256 个码位一一对应
+    bytes.iter().map(|&byte| byte as char).collect()
+}
+
+fn decode_utf8(bytes: &[u8], lossy: bool) -> XCapResult<String> {
+    if lossy {
+        Ok(String::from_utf8_lossy(bytes).to_string())
+    } else {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+/// Character sets that can be designated to GL or GR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Charset {
+    /// ISO-8859-1 left half, ESC ( B
+    Ascii,
+    /// ISO-8859-1 right half, ESC - A
+    Latin1,
+    /// Any other set, only the number of bytes per character is known
+    Unsupported(usize),
+}
+
+struct CompoundTextDecoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    lossy: bool,
+    gl: Charset,
+    gr: Charset,
+    text: String,
+}
+
+impl CompoundTextDecoder<'_> {
+    fn unsupported(&mut self, message: &str) -> XCapResult<()> {
+        if !self.lossy {
+            return Err(XCapError::new(format!(
+                "Decode compound text failed: {message} at byte {}",
+                self.position
+            )));
+        }
+
+        self.text.push(char::REPLACEMENT_CHARACTER);
+
+        Ok(())
+    }
+
+    fn decode(mut self) -> XCapResult<String> {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            match byte {
+                ESC => self.escape_sequence()?,
+                CSI => self.control_sequence(),
+                b'\t' | b'\n' => {
+                    self.text.push(byte as char);
+                    self.position += 1;
+                }
+                0x20..=0x7f => self.graphic_character(self.gl)?,
+                0xa0..=0xff => self.graphic_character(self.gr)?,
+                _ => {
+                    self.unsupported("invalid control character")?;
+                    self.position += 1;
+                }
+            }
+        }
+
+        Ok(self.text)
+    }
+
+    fn graphic_character(&mut self, charset: Charset) -> XCapResult<()> {
+        let byte = self.bytes[self.position];
+
+        match charset {
+            Charset::Ascii => {
+                self.text.push((byte & 0x7f) as char);
+                self.position += 1;
+            }
+            Charset::Latin1 => {
+                self.text.push((byte | 0x80) as char);
+                self.position += 1;
+            }
+            Charset::Unsupported(width) => {
+                self.unsupported("unsupported character set")?;
+                self.position += width;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// CSI 用于切换书写方向，跳过参数、中间字节和结束字节
+    fn control_sequence(&mut self) {
+        self.position += 1;
+
+        while let Some(&byte) = self.bytes.get(self.position) {
+            self.position += 1;
+            if (0x40..=0x7e).contains(&byte) {
+                break;
+            }
+        }
+    }
+
+    fn escape_sequence(&mut self) -> XCapResult<()> {
+        let start = self.position;
+        self.position += 1;
+
+        // ESC 之后是若干中间字节 (0x20-0x2f) 和一个结束字节 (0x30-0x7e)
+        let intermediates_start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| (0x20..=0x2f).contains(byte))
+        {
+            self.position += 1;
+        }
+        let intermediates = &self.bytes[intermediates_start..self.position];
+
+        let Some(&final_byte) = self.bytes.get(self.position) else {
+            self.position = start;
+            self.unsupported("truncated escape sequence")?;
+            self.position = self.bytes.len();
+            return Ok(());
+        };
+        self.position += 1;
+
+        match (intermediates, final_byte) {
+            // 94 字符集指定到 GL
+            (b"(", b'B') => self.gl = Charset::Ascii,
+            // JIS X0201 Roman 与 ASCII 基本一致
+            (b"(", b'J') => self.gl = Charset::Ascii,
+            (b"(", _) => self.gl = Charset::Unsupported(1),
+            // 94 / 96 字符集指定到 GR
+            (b"-", b'A') => self.gr = Charset::Latin1,
+            (b")" | b"-", _) => self.gr = Charset::Unsupported(1),
+            // 94^N 多字节字符集，例如 GB2312、JIS X0208、KS C5601
+            (b"$(", _) => self.gl = Charset::Unsupported(2),
+            (b"$)", _) => self.gr = Charset::Unsupported(2),
+            // XFree86 扩展：ESC % G 之后直到 ESC % @ 都是 UTF-8
+            (b"%", b'G') => self.utf8_segment()?,
+            (b"%", b'@') => {}
+            // 扩展段：ESC % / F M L encoding-name STX bytes
+            (b"%/", b'0'..=b'4') => self.extended_segment()?,
+            _ => {
+                self.position = start;
+                self.unsupported("unsupported escape sequence")?;
+                self.position = start + 1 + intermediates.len() + 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn utf8_segment(&mut self) -> XCapResult<()> {
+        let rest = &self.bytes[self.position..];
+        let end = rest
+            .windows(3)
+            .position(|window| window == [ESC, b'%', b'@'])
+            .unwrap_or(rest.len());
+
+        let text = decode_utf8(&rest[..end], self.lossy)?;
+        self.text.push_str(&text);
+        self.position += end;
+
+        Ok(())
+    }
+
+    fn extended_segment(&mut self) -> XCapResult<()> {
+        let (Some(&m), Some(&l)) = (
+            self.bytes.get(self.position),
+            self.bytes.get(self.position + 1),
+        ) else {
+            self.unsupported("truncated extended segment")?;
+            self.position = self.bytes.len();
+            return Ok(());
+        };
+        self.position += 2;
+
+        // 长度包含编码名称和 STX
+        let length = ((m & 0x7f) as usize) * 128 + (l & 0x7f) as usize;
+        let end = (self.position + length).min(self.bytes.len());
+        let segment = &self.bytes[self.position..end];
+        self.position = end;
+
+        let Some(name_end) = segment.iter().position(|&byte| byte == STX) else {
+            return self.unsupported("invalid extended segment");
+        };
+        let name = String::from_utf8_lossy(&segment[..name_end]);
+        let data = &segment[name_end + 1..];
+
+        if name.eq_ignore_ascii_case("utf-8") || name.eq_ignore_ascii_case("iso10646-1") {
+            let text = decode_utf8(data, self.lossy)?;
+            self.text.push_str(&text);
+            Ok(())
+        } else if name.eq_ignore_ascii_case("iso8859-1") {
+            self.text.push_str(&decode_latin1(data));
+            Ok(())
+        } else {
+            self.unsupported("unsupported extended segment encoding")
+        }
+    }
+}
+
+fn decode_compound_text(bytes: &[u8], lossy: bool) -> XCapResult<String> {
+    // 初始状态：GL 为 ASCII，GR 为 ISO-8859-1 右半部分
+    let decoder = CompoundTextDecoder {
+        bytes,
+        position: 0,
+        lossy,
+        gl: Charset::Ascii,
+        gr: Charset::Latin1,
+        text: String::new(),
+    };
+
+    decoder.decode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_string_as_latin1() {
+        let text = decode_text(TextEncoding::String, b"Caf\xe9 cr\xe8me", false).unwrap();
+        assert_eq!(text, "Café crème");
+    }
+
+    #[test]
+    fn decode_utf8_string() {
+        let bytes = "Café".as_bytes();
+        assert_eq!(
+            decode_text(TextEncoding::Utf8String, bytes, false).unwrap(),
+            "Café"
+        );
+
+        assert!(decode_text(TextEncoding::Utf8String, b"Caf\xe9", false).is_err());
+        assert_eq!(
+            decode_text(TextEncoding::Utf8String, b"Caf\xe9", true).unwrap(),
+            "Caf\u{fffd}"
+        );
+    }
+
+    #[test]
+    fn decode_compound_text() {
+        // 默认状态下 GR 即为 Latin-1
+        let text = decode_text(TextEncoding::CompoundText, b"Caf\xe9", false).unwrap();
+        assert_eq!(text, "Café");
+
+        // 显式指定字符集
+        let text = decode_text(
+            TextEncoding::CompoundText,
+            b"\x1b(B\x1b-ACaf\xe9\tna\xefve",
+            false,
+        )
+        .unwrap();
+        assert_eq!(text, "Café\tnaïve");
+
+        // ESC % G UTF-8 段
+        let mut bytes = b"a \x1b%G".to_vec();
+        bytes.extend_from_slice("日本語".as_bytes());
+        bytes.extend_from_slice(b"\x1b%@ b");
+        let text = decode_text(TextEncoding::CompoundText, &bytes, false).unwrap();
+        assert_eq!(text, "a 日本語 b");
+
+        // 扩展段
+        let mut bytes = b"\x1b%/1\x80\x88utf-8\x02".to_vec();
+        bytes.extend_from_slice("ü".as_bytes());
+        let text = decode_text(TextEncoding::CompoundText, &bytes, false).unwrap();
+        assert_eq!(text, "ü");
+    }
+
+    #[test]
+    fn decode_compound_text_lossy() {
+        // GB2312 指定到 GL
+        let bytes = b"a\x1b$(A\x30\x21\x1b(Bb";
+
+        assert!(decode_text(TextEncoding::CompoundText, bytes, false).is_err());
+        assert_eq!(
+            decode_text(TextEncoding::CompoundText, bytes, true).unwrap(),
+            "a\u{fffd}b"
+        );
+    }
+}
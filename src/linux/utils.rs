@@ -1,28 +1,35 @@
+use async_io::Timer;
+use futures_lite::{FutureExt, Stream, StreamExt, future};
 use image::{RgbaImage, open};
 use lazy_static::lazy_static;
-use log::info;
+use log::{info, warn};
 use percent_encoding::percent_decode_str;
 use serde::Deserialize;
 use std::sync::LazyLock;
+use std::time::Duration;
 use std::{
     env::{self, var_os},
+    fmt,
     path::{Path, PathBuf},
 };
 use std::ops::Deref;
 use url::Url;
 use xcb::{
-    ConnResult, Connection as XcbConnection, Xid,
+    ConnResult, Connection as XcbConnection, Xid, composite,
     randr::{GetMonitors, MonitorInfoBuf, Output},
-    x::{Atom, InternAtom, ScreenBuf},
+    x::{Atom, GetSelectionOwner, InternAtom, QueryExtension, QueryPointer, ScreenBuf},
 };
 use zbus::{
-    Result as ZBusResult,
+    Result as ZBusResult, block_on,
     blocking::{Connection as ZBusConnection, Proxy},
     zvariant,
     zvariant::Type,
 };
 
-use crate::{XCapError, error::XCapResult};
+use crate::{
+    ErrorKind, XCapError, error::XCapResult, platform::dbus::request::RequestProxy,
+    video_recorder::CancelHandle,
+};
 
 lazy_static! {
     static ref XCB_CONNECTION_AND_INDEX: ConnResult<(XcbConnection, i32)> = {
@@ -65,7 +72,12 @@ impl Deref for Connection {
 }
 
 pub fn get_xcb_connection_and_index() -> XCapResult<&'static (XcbConnection, i32)> {
-    XCB_CONNECTION_AND_INDEX.as_ref().map_err(XCapError::new)
+    XCB_CONNECTION_AND_INDEX
+        .as_ref()
+        .map_err(|err| XCapError::Unavailable {
+            message: err.to_string(),
+            source: None,
+        })
 }
 
 pub fn get_zbus_connection() -> &'static Connection {
@@ -94,7 +106,7 @@ pub fn get_current_screen_buf() -> XCapResult<ScreenBuf> {
     let screen = setup
         .roots()
         .nth(*index as usize)
-        .ok_or_else(|| XCapError::new("Not found screen"))?;
+        .ok_or_else(|| XCapError::not_found("Not found screen"))?;
 
     Ok(screen.to_owned())
 }
@@ -120,7 +132,7 @@ pub fn get_monitor_info_buf(output: Output) -> XCapResult<MonitorInfoBuf> {
             }
         }
     }
-    Err(XCapError::new("Not found monitor"))
+    Err(XCapError::not_found("Not found monitor"))
 }
 
 pub fn get_atom(name: &str) -> XCapResult<Atom> {
@@ -139,6 +151,64 @@ pub fn get_atom(name: &str) -> XCapResult<Atom> {
     Ok(atom)
 }
 
+/// 指针在 root 窗口中的位置，即全局坐标
+pub fn get_cursor_position() -> XCapResult<(i32, i32)> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let screen_buf = get_current_screen_buf()?;
+
+    let query_pointer_cookie = conn.send_request(&QueryPointer {
+        window: screen_buf.root(),
+    });
+    let query_pointer_reply = conn.wait_for_reply(query_pointer_cookie)?;
+
+    Ok((
+        query_pointer_reply.root_x() as i32,
+        query_pointer_reply.root_y() as i32,
+    ))
+}
+
+pub fn has_extension(name: &str) -> XCapResult<bool> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let query_extension_cookie = conn.send_request(&QueryExtension {
+        name: name.as_bytes(),
+    });
+    let query_extension_reply = conn.wait_for_reply(query_extension_cookie)?;
+
+    Ok(query_extension_reply.present())
+}
+
+/// 合成管理器会持有 _NET_WM_CM_Sn 选择，并通过 Composite 扩展重定向所有顶层窗口
+///
+/// https://specifications.freedesktop.org/wm-spec/1.5/ar01s08.html#id-1.9.7
+pub fn has_compositing_manager() -> XCapResult<bool> {
+    if !has_extension("Composite")? {
+        return Ok(false);
+    }
+
+    let (conn, index) = get_xcb_connection_and_index()?;
+    let Ok(cm_atom) = get_atom(&format!("_NET_WM_CM_S{index}")) else {
+        return Ok(false);
+    };
+
+    let get_selection_owner_cookie = conn.send_request(&GetSelectionOwner { selection: cm_atom });
+    let get_selection_owner_reply = conn.wait_for_reply(get_selection_owner_cookie)?;
+    if get_selection_owner_reply.owner().is_none() {
+        return Ok(false);
+    }
+
+    // NameWindowPixmap 需要 Composite 0.2 及以上版本
+    let query_version_cookie = conn.send_request(&composite::QueryVersion {
+        client_major_version: 0,
+        client_minor_version: 4,
+    });
+    let query_version_reply = conn.wait_for_reply(query_version_cookie)?;
+
+    Ok((
+        query_version_reply.major_version(),
+        query_version_reply.minor_version(),
+    ) >= (0, 2))
+}
+
 pub(super) fn png_to_rgba_image<T>(
     filename: T,
     x: i32,
@@ -195,15 +265,17 @@ pub(super) fn get_zbus_portal_request(
     Ok(request)
 }
 
-pub(super) fn wait_zbus_response<'a, T>(request: &Proxy<'a>) -> XCapResult<T>
+pub(super) fn wait_zbus_response<'a, T>(
+    request: &Proxy<'a>,
+    options: &PortalRequestOptions,
+) -> XCapResult<T>
 where
     T: for<'de> Deserialize<'de> + Type,
 {
-    let mut response = request.receive_signal("Response")?;
+    let mut response = block_on(request.inner().receive_signal("Response"))?;
 
-    let message = response
-        .next()
-        .ok_or(XCapError::new("Failed get response"))?;
+    let request = RequestProxy::from(request.inner().clone());
+    let message = wait_portal_response(&request, &mut response, options)?;
 
     let body = message.body();
     let (code, body): (u32, T) = body.deserialize()?;
@@ -212,9 +284,97 @@ where
         return Ok(body);
     }
 
+    Err(portal_response_error(code))
+}
+
+/// portal Response 信号中表示失败的响应码，作为错误的 source 保留
+#[derive(Debug)]
+pub(crate) struct PortalResponseCode(pub u32);
+
+impl fmt::Display for PortalResponseCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "portal response code {}", self.0)
+    }
+}
+
+impl std::error::Error for PortalResponseCode {}
+
+/// 响应码 1 表示用户取消了请求 (例如关闭了对话框)，其他响应码表示请求以其他方式结束
+pub(crate) fn portal_response_error(code: u32) -> XCapError {
+    let source = Some(Box::new(PortalResponseCode(code)) as Box<_>);
+
     if code == 1 {
-        return Err(XCapError::new("Z-Bus canceled"));
+        XCapError::Cancelled {
+            message: "The user cancelled the portal request".to_string(),
+            source,
+        }
+    } else {
+        XCapError::Aborted {
+            message: format!("The portal ended the request with response code {code}"),
+            source,
+        }
     }
+}
+
+/// 等待 portal 请求时的超时和取消设置，默认一直等待
+///
+/// 只有 ScreenCast 的请求会使用录制器的设置，Screenshot 请求没有对应的公开选项，始终使用默认值
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PortalRequestOptions {
+    pub timeout: Option<Duration>,
+    pub cancel_handle: Option<CancelHandle>,
+}
+
+/// 等待 portal 请求的 Response 信号，超时或者被取消时关闭请求，portal 会随之关闭对应的对话框
+pub(crate) fn wait_portal_response<S>(
+    request: &RequestProxy<'_>,
+    responses: &mut S,
+    options: &PortalRequestOptions,
+) -> XCapResult<S::Item>
+where
+    S: Stream + Unpin,
+{
+    block_on(async {
+        let response = async {
+            responses.next().await.ok_or_else(|| XCapError::Cancelled {
+                message: "The portal closed the request without a response".to_string(),
+                source: None,
+            })
+        };
+        let timeout = async {
+            match options.timeout {
+                Some(timeout) => {
+                    Timer::after(timeout).await;
+                    Err(XCapError::Timeout {
+                        message: format!("No response from the portal within {timeout:?}"),
+                        source: None,
+                    })
+                }
+                None => future::pending().await,
+            }
+        };
+        let cancelled = async {
+            match &options.cancel_handle {
+                Some(cancel_handle) => {
+                    cancel_handle.cancelled().await;
+                    Err(XCapError::Aborted {
+                        message: "Portal request aborted".to_string(),
+                        source: None,
+                    })
+                }
+                None => future::pending().await,
+            }
+        };
+
+        let result = response.or(timeout).or(cancelled).await;
+
+        if let Err(err) = &result
+            && matches!(err.kind(), ErrorKind::Timeout | ErrorKind::Aborted)
+            && let Err(close_err) = request.close().await
+        {
+            warn!("Failed to close portal request: {close_err}");
+        }
 
-    Err(XCapError::new(format!("Response code is {code}")))
+        result
+    })
 }
@@ -9,7 +9,9 @@ use zbus::{
 
 use crate::{
     error::XCapResult,
-    platform::utils::{get_zbus_portal_request, safe_uri_to_path, wait_zbus_response},
+    platform::utils::{
+        PortalRequestOptions, get_zbus_portal_request, safe_uri_to_path, wait_zbus_response,
+    },
 };
 
 use super::utils::{get_zbus_connection, png_to_rgba_image};
@@ -80,7 +82,9 @@ fn org_freedesktop_portal_screenshot(
 
     // https://github.com/flatpak/xdg-desktop-portal/blob/main/data/org.freedesktop.portal.Screenshot.xml
     proxy.call_method("Screenshot", &("", options))?;
-    let screenshot_response: ScreenshotResponse = wait_zbus_response(&portal_request)?;
+    // 截图没有超时和取消的选项，一直等待 portal 的响应
+    let screenshot_response: ScreenshotResponse =
+        wait_zbus_response(&portal_request, &PortalRequestOptions::default())?;
     let filename = safe_uri_to_path(&screenshot_response.uri)?;
     defer!({
         let _ = fs::remove_file(&filename);
@@ -1,22 +1,26 @@
 use super::{
     dbus,
     impl_monitor::ImplMonitor,
-    utils::{get_zbus_connection, get_zbus_portal_request, wait_zbus_response},
+    impl_video_recorder::RecorderSource,
+    utils::{
+        get_zbus_connection, get_zbus_portal_request, wait_zbus_response, PortalRequestOptions,
+    },
 };
-use crate::dir::{data_dir, project_dir};
+use crate::dir::project_dir;
 use crate::platform::dbus::request::{
     on_blocking_response, request_handle_path, RequestProxyBlocking, Responses,
 };
 use crate::platform::dbus::screencast::{
     CreateSessionOption, CreateSessionResponse, CursorModes, PersistMode, ScreenCastProxyBlocking,
-    SelectSourcesOption, SourceType, StartOption, StartResponse,
+    SelectSourcesOption, SourceType, StartOption, StartResponse, StartStreamResponse,
 };
 use crate::platform::dbus::session::session_handle_path;
 use crate::platform::dbus::{generate_session_handle, generate_token_handle, screencast};
-use crate::video_recorder::Condition;
-use crate::{video_recorder::Frame, XCapError, XCapResult};
+use crate::token_store::{default_app_id, FileTokenStore, MemoryTokenStore, TokenKey, TokenStore};
+use crate::video_recorder::{self, Condition, FrameFitter, PipeWireRemote, VideoRecorderOptions};
+use crate::{video_recorder::Frame, window::Rect, XCapError, XCapResult};
 use bitflags::bitflags;
-use log::{error, info, trace};
+use log::{error, info, trace, warn};
 use pipewire::context::{ContextBox, ContextRc};
 use pipewire::main_loop::MainLoopRc;
 use pipewire::stream::{StreamBox, StreamRc};
@@ -39,9 +43,9 @@ use pipewire::{
     stream::{Stream, StreamFlags},
 };
 use std::borrow::Cow;
-use std::io::Read;
+use std::os::fd::OwnedFd;
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::{LazyLock, Mutex};
 use std::{
     collections::HashMap,
     fmt,
@@ -57,11 +61,9 @@ use zbus::zvariant::OwnedValue;
 use zbus::{
     blocking::Proxy,
     zvariant,
-    zvariant::{DeserializeDict, OwnedFd, OwnedObjectPath, Type, Value},
+    zvariant::{DeserializeDict, OwnedObjectPath, Type, Value},
 };
 
-const SCREEN_CAST_TOKEN_FILE_PATH: &str = "RESTORE_TOKEN";
-
 bitflags! {
     #[derive(PartialEq, Ord, PartialOrd, Eq, Copy, Clone)]
     struct ScreenCastFlag : u8 {
@@ -71,6 +73,126 @@ bitflags! {
     }
 }
 
+/// 流的位置和大小是否与显示器一致，portal 给出的可能是逻辑坐标，也可能是物理坐标
+fn stream_matches_monitor(
+    position: (i32, i32),
+    size: (i32, i32),
+    monitor_rect: Rect,
+    scale_factor: f32,
+) -> bool {
+    // 显示器的坐标是缩放后取整的，允许 1 像素的误差
+    let matches = |scale: f32| {
+        let near = |a: i32, b: f32| (a as f32 - b * scale).abs() <= 1.0;
+
+        near(position.0, monitor_rect.x as f32)
+            && near(position.1, monitor_rect.y as f32)
+            && near(size.0, monitor_rect.width as f32)
+            && near(size.1, monitor_rect.height as f32)
+    };
+
+    matches(1.0) || matches(scale_factor)
+}
+
+fn monitor_rect(impl_monitor: &ImplMonitor) -> XCapResult<Rect> {
+    Ok(Rect {
+        x: impl_monitor.x()?,
+        y: impl_monitor.y()?,
+        width: impl_monitor.width()?,
+        height: impl_monitor.height()?,
+    })
+}
+
+/// 找到与请求的显示器对应的流，用户在 portal 对话框中选择了其他显示器时返回错误
+fn find_monitor_stream<'s>(
+    streams: &'s [StartStreamResponse],
+    impl_monitor: &ImplMonitor,
+) -> XCapResult<&'s StartStreamResponse> {
+    let monitor_rect = monitor_rect(impl_monitor)?;
+    let scale_factor = impl_monitor.scale_factor()?;
+
+    let mut unverified = None;
+    for stream in streams {
+        match (stream.property.position, stream.property.size) {
+            (Some(position), Some(size)) => {
+                if stream_matches_monitor(position, size, monitor_rect, scale_factor) {
+                    return Ok(stream);
+                }
+            }
+            _ => {
+                unverified.get_or_insert(stream);
+            }
+        }
+    }
+
+    // 旧版本的 portal 不提供流的位置和大小，无法确认用户选择的显示器
+    if let Some(stream) = unverified {
+        warn!(
+            "The portal did not report the stream position, assuming it is the requested monitor"
+        );
+        return Ok(stream);
+    }
+
+    let picked = streams
+        .iter()
+        .filter_map(|stream| Some((stream.property.position?, stream.property.size?)))
+        .map(|((x, y), (width, height))| format!("{width}x{height} at ({x}, {y})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Err(XCapError::not_found(format!(
+        "The monitor picked in the portal dialog ({picked}) is not the requested monitor {} ({}x{} at ({}, {}))",
+        impl_monitor.name()?,
+        monitor_rect.width,
+        monitor_rect.height,
+        monitor_rect.x,
+        monitor_rect.y
+    )))
+}
+
+/// 找到流对应的显示器
+fn stream_monitor(
+    stream: &StartStreamResponse,
+    impl_monitors: &[ImplMonitor],
+) -> XCapResult<ImplMonitor> {
+    let (Some(position), Some(size)) = (stream.property.position, stream.property.size) else {
+        // 只有一个显示器时不需要位置就能确定
+        if let [impl_monitor] = impl_monitors {
+            return Ok(impl_monitor.clone());
+        }
+
+        return Err(XCapError::new(format!(
+            "The portal did not report the position of stream {}",
+            stream.pipewire_node_id
+        )));
+    };
+
+    for impl_monitor in impl_monitors {
+        if stream_matches_monitor(
+            position,
+            size,
+            monitor_rect(impl_monitor)?,
+            impl_monitor.scale_factor()?,
+        ) {
+            return Ok(impl_monitor.clone());
+        }
+    }
+
+    Err(XCapError::not_found(format!(
+        "No monitor is {}x{} at ({}, {}) like stream {}",
+        size.0, size.1, position.0, position.1, stream.pipewire_node_id
+    )))
+}
+
+/// PersistMode::Transient 的 restore token 只在进程运行期间保存在内存中，不写入磁盘
+static TRANSIENT_TOKEN_STORE: LazyLock<Arc<dyn TokenStore>> =
+    LazyLock::new(|| Arc::new(MemoryTokenStore::new()));
+
+/// restore token 的存储位置和键
+struct RestoreTokenStorage {
+    store: Arc<dyn TokenStore>,
+    key: TokenKey,
+}
+
 /// https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.ScreenCast.html
 pub struct ScreenCast<'a> {
     proxy: ScreenCastProxyBlocking<'a>,
@@ -78,49 +200,60 @@ pub struct ScreenCast<'a> {
     sources: SourceType,
     cursor_modes: CursorModes,
     restore_token: Option<String>,
+    persist_mode: PersistMode,
+    token_storage: Option<RestoreTokenStorage>,
+    request_options: PortalRequestOptions,
 }
 
 impl ScreenCast<'_> {
-    fn new(flags: ScreenCastFlag, sources: SourceType) -> XCapResult<Self> {
+    fn new(
+        flags: ScreenCastFlag,
+        sources: SourceType,
+        persist_mode: PersistMode,
+        token_storage: Option<RestoreTokenStorage>,
+        request_options: PortalRequestOptions,
+    ) -> XCapResult<Self> {
         let conn = get_zbus_connection();
         let proxy = ScreenCastProxyBlocking::new(&conn)?;
 
         let v = proxy.version()?;
         let modes = if flags.contains(ScreenCastFlag::HideCursor) {
             if v < 2 {
-                return Err(XCapError::new(format!(
-                    "Version {} does not have capability to fetch cursor modes",
-                    v
-                )));
+                return Err(XCapError::Unavailable {
+                    message: format!(
+                        "Version {} does not have capability to fetch cursor modes",
+                        v
+                    ),
+                    source: None,
+                });
             }
 
             let modes = proxy.available_cursor_modes()?;
             if !modes.is_hidden_available() {
-                return Err(XCapError::new("Cursor hiding is not supported"));
+                return Err(XCapError::Unavailable {
+                    message: "Cursor hiding is not supported".to_string(),
+                    source: None,
+                });
             }
             modes
         } else {
             CursorModes::empty()
         };
 
-        let restore_token = if flags.contains(ScreenCastFlag::SavePermission) {
+        let restore_token = if let Some(token_storage) = &token_storage
+            && flags.contains(ScreenCastFlag::SavePermission)
+        {
             if v < 4 {
-                return Err(XCapError::new(format!(
-                    "Version {} does not have capability to save screen cast permission",
-                    v
-                )));
+                return Err(XCapError::Unavailable {
+                    message: format!(
+                        "Version {} does not have capability to save screen cast permission",
+                        v
+                    ),
+                    source: None,
+                });
             }
 
-            let path = data_dir().join(SCREEN_CAST_TOKEN_FILE_PATH);
-            if path.is_file() {
-                let mut result = String::new();
-                let mut file = std::fs::File::open(path)?;
-                file.read_to_string(&mut result)?;
-
-                Some(result)
-            } else {
-                None
-            }
+            token_storage.store.load(&token_storage.key)?
         } else {
             None
         };
@@ -131,6 +264,9 @@ impl ScreenCast<'_> {
             sources,
             cursor_modes: modes,
             restore_token,
+            persist_mode,
+            token_storage,
+            request_options,
         })
     }
 
@@ -141,7 +277,7 @@ impl ScreenCast<'_> {
         let session_handle_token = generate_session_handle();
 
         let resp: Responses<CreateSessionResponse> =
-            on_blocking_response(conn, handle_token.as_str(), || {
+            on_blocking_response(conn, handle_token.as_str(), &self.request_options, || {
                 self.proxy.create_session(CreateSessionOption {
                     handle_token: &handle_token,
                     session_handle_token: &session_handle_token,
@@ -151,10 +287,7 @@ impl ScreenCast<'_> {
             })?;
 
         if !resp.is_success() {
-            return Err(XCapError::new(format!(
-                "got error response from portal: {}",
-                resp.code
-            )));
+            return Err(resp.code.to_error());
         }
 
         let session_path = session_handle_path(&conn, &session_handle_token)?;
@@ -169,38 +302,35 @@ impl ScreenCast<'_> {
         let conn = get_zbus_connection();
         let handle_token = generate_token_handle();
 
-        let resp: Responses<()> = on_blocking_response(conn, handle_token.as_str(), || {
-            let restore_token = if let Some(ref token) = self.restore_token
-                && self.flags.contains(ScreenCastFlag::SavePermission)
-            {
-                Some(token.clone())
-            } else {
-                None
-            };
-
-            const PERSIST_MODE: PersistMode = PersistMode::None;
-            self.proxy.select_sources(
-                session.as_ref(),
-                SelectSourcesOption {
-                    handle_token: &handle_token,
-                    types: self.sources,
-                    multiple: self.flags.contains(ScreenCastFlag::EnableMulti),
-                    cursor_mode: self
-                        .cursor_modes
-                        .best_mode(self.flags.contains(ScreenCastFlag::HideCursor)),
-                    restore_token: restore_token.as_deref(),
-                    persist_mode: PERSIST_MODE,
-                },
-            )?;
+        let resp: Responses<()> =
+            on_blocking_response(conn, handle_token.as_str(), &self.request_options, || {
+                let restore_token = if let Some(ref token) = self.restore_token
+                    && self.flags.contains(ScreenCastFlag::SavePermission)
+                {
+                    Some(token.clone())
+                } else {
+                    None
+                };
+
+                self.proxy.select_sources(
+                    session.as_ref(),
+                    SelectSourcesOption {
+                        handle_token: &handle_token,
+                        types: self.sources,
+                        multiple: self.flags.contains(ScreenCastFlag::EnableMulti),
+                        cursor_mode: self
+                            .cursor_modes
+                            .best_mode(self.flags.contains(ScreenCastFlag::HideCursor)),
+                        restore_token: restore_token.as_deref(),
+                        persist_mode: self.persist_mode,
+                    },
+                )?;
 
-            Ok(())
-        })?;
+                Ok(())
+            })?;
 
         if !resp.is_success() {
-            return Err(XCapError::new(format!(
-                "got error response from portal: {}",
-                resp.code
-            )));
+            return Err(resp.code.to_error());
         }
 
         Ok(())
@@ -215,7 +345,7 @@ impl ScreenCast<'_> {
         let handle_token = generate_token_handle();
 
         let resp: Responses<StartResponse> =
-            on_blocking_response(conn, handle_token.as_str(), || {
+            on_blocking_response(conn, handle_token.as_str(), &self.request_options, || {
                 self.proxy.start(
                     session.as_ref(),
                     window_handle.unwrap_or(""),
@@ -227,21 +357,42 @@ impl ScreenCast<'_> {
                 Ok(())
             })?;
 
+        // 取消或者失败时保留 token，下次还可以再用
         if !resp.is_success() {
-            return Err(XCapError::new(format!(
-                "got error response from portal: {}",
-                resp.code
-            )));
+            return Err(resp.code.to_error());
+        }
+
+        // restore token 只能使用一次，portal 每次都会返回一个新的 token
+        if let Some(token_storage) = &self.token_storage
+            && self.flags.contains(ScreenCastFlag::SavePermission)
+        {
+            match &resp.restore_token {
+                Some(token) => token_storage.store.store(&token_storage.key, token)?,
+                // 发送了 token 却没有拿到新的，说明 portal 拒绝了旧的 token
+                None if self.restore_token.is_some() => {
+                    token_storage.store.remove(&token_storage.key)?
+                }
+                None => {}
+            }
         }
 
         Ok(resp.results().clone())
     }
+
+    /// portal 为会话开放的 PipeWire 连接，沙盒中只有通过它才能看到流节点
+    pub fn open_pipewire_remote(&self, session: &OwnedObjectPath) -> XCapResult<OwnedFd> {
+        let fd = self
+            .proxy
+            .open_pipe_wire_remote(session.as_ref(), HashMap::new())?;
+
+        Ok(fd.into())
+    }
 }
 
 #[derive(Clone)]
 pub struct WaylandVideoRecorder {
     #[allow(dead_code)]
-    monitor: ImplMonitor,
+    source: RecorderSource,
     // sender: Sender<Frame>,
     condition: Arc<Mutex<Condition>>,
     condition_sender: channel::Sender<Condition>,
@@ -250,7 +401,7 @@ pub struct WaylandVideoRecorder {
 impl fmt::Debug for WaylandVideoRecorder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("WaylandVideoRecorder")
-            .field("monitor", &self.monitor)
+            .field("source", &self.source)
             // .field("sender", &self.sender)
             .field("is_running", &self.condition)
             // Sender is not Debug
@@ -264,34 +415,172 @@ struct ListenerUserData {
     pub format: VideoInfoRaw,
 }
 
-impl WaylandVideoRecorder {
-    pub fn new(monitor: ImplMonitor) -> XCapResult<(Self, Receiver<Frame>)> {
-        let (frame_sender, frame_receiver) = mpsc::channel();
-        let (cond_sender, cond_receiver) = channel::channel();
+/// 开始录制的 portal 会话，以及用户在对话框中选择的流
+struct ScreenCastSession {
+    screen_cast: ScreenCast<'static>,
+    session: OwnedObjectPath,
+    streams: Vec<StartStreamResponse>,
+    pipewire_remote: PipeWireRemote,
+}
 
-        const FLAGS: ScreenCastFlag = ScreenCastFlag::empty();
-        const SOURCES: SourceType = SourceType::Monitor;
-        let screen_cast = ScreenCast::new(FLAGS, SOURCES)?;
-        let session = screen_cast.create_session()?;
-        screen_cast.select_sources(&session)?;
-        let response = screen_cast.start(None, &session)?;
+impl ScreenCastSession {
+    /// 每次调用都会让 portal 开放一个新的 PipeWire 连接，使用默认连接时返回 None
+    fn open_pipewire_remote(&self) -> XCapResult<Option<OwnedFd>> {
+        match self.pipewire_remote {
+            PipeWireRemote::Portal => {
+                Ok(Some(self.screen_cast.open_pipewire_remote(&self.session)?))
+            }
+            PipeWireRemote::Default => Ok(None),
+        }
+    }
+}
+
+/// 通过 portal 开始录制
+fn start_screen_cast<F>(
+    sources: SourceType,
+    multiple: bool,
+    options: VideoRecorderOptions,
+    token_source: F,
+) -> XCapResult<ScreenCastSession>
+where
+    F: FnOnce() -> XCapResult<String>,
+{
+    // portal 的窗口流本身会跟随窗口，但 Wayland 下无法获取全局的鼠标位置
+    if options.follow_cursor.is_some() {
+        return Err(XCapError::NotSupported);
+    }
+
+    let (mut flags, persist_mode) = match options.persist_mode {
+        video_recorder::PersistMode::None => (ScreenCastFlag::empty(), PersistMode::None),
+        video_recorder::PersistMode::Transient => {
+            (ScreenCastFlag::SavePermission, PersistMode::Session)
+        }
+        video_recorder::PersistMode::Persistent => {
+            (ScreenCastFlag::SavePermission, PersistMode::System)
+        }
+    };
+    if multiple {
+        flags |= ScreenCastFlag::EnableMulti;
+    }
+
+    let request_options = PortalRequestOptions {
+        timeout: options.portal_timeout,
+        cancel_handle: options.cancel_handle,
+    };
+    let token_storage = if flags.contains(ScreenCastFlag::SavePermission) {
+        let app_id = options.app_id.unwrap_or_else(default_app_id);
+
+        Some(RestoreTokenStorage {
+            store: match (options.persist_mode, options.token_store) {
+                (video_recorder::PersistMode::Transient, _) => TRANSIENT_TOKEN_STORE.clone(),
+                (_, Some(token_store)) => token_store.0,
+                (_, None) => Arc::new(FileTokenStore::default()),
+            },
+            key: TokenKey::new(app_id, token_source()?),
+        })
+    } else {
+        None
+    };
+    let screen_cast =
+        ScreenCast::new(flags, sources, persist_mode, token_storage, request_options)?;
+    let session = screen_cast.create_session()?;
+    screen_cast.select_sources(&session)?;
+    let response = screen_cast.start(None, &session)?;
+
+    let streams = response
+        .streams
+        .filter(|streams| !streams.is_empty())
+        .ok_or(XCapError::new("Stream ID not found"))?;
+
+    Ok(ScreenCastSession {
+        screen_cast,
+        session,
+        streams,
+        pipewire_remote: options.pipewire_remote,
+    })
+}
+
+impl WaylandVideoRecorder {
+    pub fn new(
+        source: RecorderSource,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
+        // Wayland 下无法指定具体的窗口，由用户在 portal 的对话框中选择要录制的窗口
+        let sources = match source {
+            RecorderSource::Monitor(_) => SourceType::Monitor,
+            RecorderSource::Window(_) => SourceType::Window,
+        };
+        let frame_fitter = FrameFitter::new(&options);
+        let screen_cast_session = start_screen_cast(sources, false, options, || match &source {
+            RecorderSource::Monitor(impl_monitor) => {
+                Ok(format!("monitor-{}", impl_monitor.name()?))
+            }
+            RecorderSource::Window(_) => Ok("window".to_string()),
+        })?;
 
         // 获取流节点ID
-        let stream_id = response
+        let streams = &screen_cast_session.streams;
+        let stream = match &source {
+            RecorderSource::Monitor(impl_monitor) => find_monitor_stream(streams, impl_monitor)?,
+            RecorderSource::Window(_) => &streams[0],
+        };
+        let remote_fd = screen_cast_session.open_pipewire_remote()?;
+
+        Self::from_stream(source, stream.pipewire_node_id, remote_fd, frame_fitter)
+    }
+
+    /// 在同一个 portal 会话中录制用户选择的所有显示器，只需要确认一次
+    pub fn new_monitors(
+        options: VideoRecorderOptions,
+    ) -> XCapResult<Vec<(ImplMonitor, Self, Receiver<Frame>)>> {
+        let frame_fitter = FrameFitter::new(&options);
+        let screen_cast_session = start_screen_cast(SourceType::Monitor, true, options, || {
+            Ok("monitors".to_string())
+        })?;
+        let impl_monitors = ImplMonitor::all()?;
+
+        screen_cast_session
             .streams
-            .ok_or(XCapError::new("Stream ID not found"))?
-            .first()
-            .ok_or(XCapError::new("Stream ID not found"))?
-            .pipewire_node_id;
+            .iter()
+            .map(|stream| {
+                let impl_monitor = stream_monitor(stream, &impl_monitors)?;
+                // 每个录制器都向 portal 请求自己的 PipeWire 连接
+                let remote_fd = screen_cast_session.open_pipewire_remote()?;
+                let (recorder, receiver) = Self::from_stream(
+                    RecorderSource::Monitor(impl_monitor.clone()),
+                    stream.pipewire_node_id,
+                    remote_fd,
+                    frame_fitter,
+                )?;
+
+                Ok((impl_monitor, recorder, receiver))
+            })
+            .collect()
+    }
+
+    fn from_stream(
+        source: RecorderSource,
+        stream_id: u32,
+        remote_fd: Option<OwnedFd>,
+        frame_fitter: FrameFitter,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
+        let (frame_sender, frame_receiver) = mpsc::channel();
+        let (cond_sender, cond_receiver) = channel::channel();
 
         let recorder = Self {
-            monitor,
+            source,
             // sender,
             condition: Arc::new(Mutex::new(Condition::Init)),
             condition_sender: cond_sender,
         };
 
-        recorder.pipewire_capturer(stream_id, frame_sender, cond_receiver)?;
+        recorder.pipewire_capturer(
+            stream_id,
+            remote_fd,
+            frame_fitter,
+            frame_sender,
+            cond_receiver,
+        )?;
 
         Ok((recorder, frame_receiver))
     }
@@ -299,6 +588,8 @@ impl WaylandVideoRecorder {
     pub fn pipewire_capturer(
         &self,
         stream_id: u32,
+        remote_fd: Option<OwnedFd>,
+        frame_fitter: FrameFitter,
         sender: mpsc::Sender<Frame>,
         condition_receiver: channel::Receiver<Condition>,
     ) -> XCapResult<()> {
@@ -309,7 +600,10 @@ impl WaylandVideoRecorder {
         thread::spawn(move || {
             let main_loop = MainLoopRc::new(None)?;
             let context = ContextRc::new(&main_loop, None)?;
-            let core = context.connect_rc(None)?;
+            let core = match remote_fd {
+                Some(remote_fd) => context.connect_fd_rc(remote_fd, None)?,
+                None => context.connect_rc(None)?,
+            };
 
             let user_data = ListenerUserData {
                 format: Default::default(),
@@ -405,7 +699,8 @@ impl WaylandVideoRecorder {
                             };
 
                             if state.is_running() {
-                                let _ = sender.send(Frame::new(size.width, size.height, buffer));
+                                let frame = Frame::new(size.width, size.height, buffer);
+                                let _ = sender.send(frame_fitter.fit(frame));
                             }
                         }
                     }
@@ -561,3 +856,27 @@ impl Drop for WaylandVideoRecorder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_matches_logical_or_physical_monitor_rect() {
+        let monitor_rect = Rect {
+            x: 1280,
+            y: 0,
+            width: 1280,
+            height: 720,
+        };
+
+        let matches = |position, size, scale_factor| {
+            stream_matches_monitor(position, size, monitor_rect, scale_factor)
+        };
+
+        assert!(matches((1280, 0), (1280, 720), 1.5));
+        assert!(matches((1920, 0), (1920, 1080), 1.5));
+        assert!(!matches((0, 0), (1280, 720), 1.5));
+        assert!(!matches((1280, 0), (1920, 1080), 1.0));
+    }
+}
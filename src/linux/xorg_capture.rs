@@ -1,11 +1,15 @@
 use image::RgbaImage;
+use log::warn;
 use xcb::{
     Connection,
-    x::{Drawable, GetImage, ImageFormat, ImageOrder, Window},
+    composite::NameWindowPixmap,
+    x::{Drawable, FreePixmap, GetImage, ImageFormat, ImageOrder, Pixmap, Window},
 };
 
 use crate::error::{XCapError, XCapResult};
 
+use super::utils::get_xcb_connection_and_index;
+
 fn get_pixel8_rgba(
     bytes: &[u8],
     x: u32,
@@ -69,20 +73,88 @@ fn get_pixel24_32_rgba(
     }
 }
 
-pub fn xorg_capture(
-    window: Window,
+/// 32 位深度的 ARGB visual 中颜色值是预乘过 alpha 的，需要还原
+fn unpremultiply(color: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        return 0;
+    }
+
+    (color as u32 * 255 / alpha as u32).min(255) as u8
+}
+
+fn read_pixel32(
+    bytes: &[u8],
+    x: u32,
+    y: u32,
+    width: u32,
+    bits_per_pixel: u32,
+    bit_order: ImageOrder,
+) -> (u8, u8, u8, u8) {
+    let index = ((y * width + x) * bits_per_pixel / 8) as usize;
+
+    if bit_order == ImageOrder::LsbFirst {
+        (
+            bytes[index + 2],
+            bytes[index + 1],
+            bytes[index],
+            bytes[index + 3],
+        )
+    } else {
+        (
+            bytes[index + 1],
+            bytes[index + 2],
+            bytes[index + 3],
+            bytes[index],
+        )
+    }
+}
+
+/// 只有合成管理器的 ARGB 窗口 pixmap 才有真实的 alpha，其他 32 位深度的图像 alpha 字节没有意义
+fn get_pixel32_opaque_rgba(
+    bytes: &[u8],
+    x: u32,
+    y: u32,
+    width: u32,
+    bits_per_pixel: u32,
+    bit_order: ImageOrder,
+) -> (u8, u8, u8, u8) {
+    let (r, g, b, _) = read_pixel32(bytes, x, y, width, bits_per_pixel, bit_order);
+
+    (r, g, b, 255)
+}
+
+fn get_pixel32_rgba(
+    bytes: &[u8],
+    x: u32,
+    y: u32,
+    width: u32,
+    bits_per_pixel: u32,
+    bit_order: ImageOrder,
+) -> (u8, u8, u8, u8) {
+    let (r, g, b, a) = read_pixel32(bytes, x, y, width, bits_per_pixel, bit_order);
+
+    (
+        unpremultiply(r, a),
+        unpremultiply(g, a),
+        unpremultiply(b, a),
+        a,
+    )
+}
+
+fn get_image(
+    conn: &Connection,
+    drawable: Drawable,
     x: i32,
     y: i32,
     width: u32,
     height: u32,
+    keep_alpha: bool,
 ) -> XCapResult<RgbaImage> {
-    let (conn, _) = Connection::connect(None)?;
-
     let setup = conn.get_setup();
 
     let get_image_cookie = conn.send_request(&GetImage {
         format: ImageFormat::ZPixmap,
-        drawable: Drawable::Window(window),
+        drawable,
         x: x as i16,
         y: y as i16,
         width: width as u16,
@@ -107,7 +179,8 @@ pub fn xorg_capture(
         8 => get_pixel8_rgba,
         16 => get_pixel16_rgba,
         24 => get_pixel24_32_rgba,
-        32 => get_pixel24_32_rgba,
+        32 if keep_alpha => get_pixel32_rgba,
+        32 => get_pixel32_opaque_rgba,
         _ => return Err(XCapError::new(format!("Unsupported {depth} depth"))),
     };
 
@@ -127,3 +200,72 @@ pub fn xorg_capture(
     RgbaImage::from_raw(width, height, rgba)
         .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
 }
+
+pub fn xorg_capture(
+    window: Window,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> XCapResult<RgbaImage> {
+    let (conn, _) = Connection::connect(None)?;
+
+    get_image(&conn, Drawable::Window(window), x, y, width, height, false)
+}
+
+/// 读取被合成管理器重定向的顶层窗口的离屏 pixmap，截图不受其他窗口遮挡的影响
+///
+/// https://cgit.freedesktop.org/xorg/proto/compositeproto/tree/compositeproto.txt
+pub fn xorg_capture_window_pixmap(
+    window: Window,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> XCapResult<RgbaImage> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+
+    // pixmap 必须和 NameWindowPixmap 使用同一个连接
+    let pixmap: Pixmap = conn.generate_id();
+    conn.send_and_check_request(&NameWindowPixmap { window, pixmap })
+        .map_err(xcb::Error::Protocol)?;
+
+    let result = get_image(conn, Drawable::Pixmap(pixmap), x, y, width, height, true);
+
+    // 无论截图是否成功都要释放 pixmap，释放失败不影响截图结果
+    if let Err(err) = conn.send_and_check_request(&FreePixmap { pixmap }) {
+        warn!("Failed to free window pixmap: {err}");
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_pixel32_rgba_keeps_alpha() {
+        // 预乘后的 BGRA：半透明红色
+        let bytes = [0x00, 0x00, 0x80, 0x80, 0x00, 0x00, 0x00, 0x00];
+
+        assert_eq!(
+            get_pixel32_rgba(&bytes, 0, 0, 2, 32, ImageOrder::LsbFirst),
+            (0xFF, 0x00, 0x00, 0x80)
+        );
+        assert_eq!(
+            get_pixel32_rgba(&bytes, 1, 0, 2, 32, ImageOrder::LsbFirst),
+            (0x00, 0x00, 0x00, 0x00)
+        );
+    }
+
+    #[test]
+    fn get_pixel32_opaque_rgba_ignores_alpha() {
+        let bytes = [0x10, 0x20, 0x30, 0x00];
+
+        assert_eq!(
+            get_pixel32_opaque_rgba(&bytes, 0, 0, 1, 32, ImageOrder::LsbFirst),
+            (0x30, 0x20, 0x10, 0xFF)
+        );
+    }
+}
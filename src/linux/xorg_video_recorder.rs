@@ -1,24 +1,307 @@
+use super::capture::{WindowGeometry, capture_window_area_with};
 use super::impl_monitor::ImplMonitor;
+use super::impl_video_recorder::RecorderSource;
+use super::impl_window::ImplWindow;
+use super::utils::{get_current_screen_buf, has_extension};
+use super::xorg_capture::xorg_capture;
 use crate::error::{XCapError, XCapResult};
-use crate::video_recorder::{Condition, Frame, RecorderWaker};
+use crate::video_recorder::{Condition, Frame, RecorderWaker, VideoRecorderOptions, fit_frame};
+use crate::window::Rect;
+use image::{Rgba, RgbaImage, imageops};
 use log::*;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use xcb::{
+    Connection, Extension,
+    shape::{self, SelectInput as ShapeSelectInput},
+    x::{ChangeWindowAttributes, Cw, Event as XEvent, EventMask, Window},
+};
+
+/// 通过 ConfigureNotify、ReparentNotify 和 ShapeNotify 跟踪窗口的位置、大小和形状，
+/// 只有窗口变化时才重新查询窗口的几何信息
+struct WindowTracker {
+    conn: Connection,
+    impl_window: ImplWindow,
+    rect: Rect,
+    geometry: WindowGeometry,
+    destroyed: bool,
+}
+
+impl WindowTracker {
+    fn new(impl_window: ImplWindow) -> XCapResult<Self> {
+        // 需要声明 SHAPE 扩展才能解析 ShapeNotify 事件
+        let (conn, _) = Connection::connect_with_extensions(None, &[], &[Extension::Shape])?;
+        let rect = impl_window.outer_rect()?;
+        let geometry = WindowGeometry::new(&impl_window)?;
+
+        let window_tracker = WindowTracker {
+            conn,
+            impl_window,
+            rect,
+            geometry,
+            destroyed: false,
+        };
+        window_tracker.select_events()?;
+
+        Ok(window_tracker)
+    }
+
+    /// 窗口管理器移动的是 frame 窗口，客户端窗口只有大小改变时才会收到真实的 ConfigureNotify
+    fn select_events(&self) -> XCapResult<()> {
+        let frame_window = self.impl_window.frame_window()?;
+        let has_shape = has_extension("SHAPE")?;
+
+        for window in [self.impl_window.window, frame_window.window] {
+            self.select_structure_notify(window)?;
+
+            if has_shape {
+                self.conn
+                    .send_and_check_request(&ShapeSelectInput {
+                        destination_window: window,
+                        enable: true,
+                    })
+                    .map_err(xcb::Error::Protocol)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn select_structure_notify(&self, window: Window) -> XCapResult<()> {
+        self.conn
+            .send_and_check_request(&ChangeWindowAttributes {
+                window,
+                value_list: &[Cw::EventMask(EventMask::STRUCTURE_NOTIFY)],
+            })
+            .map_err(xcb::Error::Protocol)?;
+
+        Ok(())
+    }
+
+    fn rect(&mut self) -> XCapResult<Rect> {
+        let mut changed = false;
+        let mut reparented = false;
+
+        while let Some(event) = self.conn.poll_for_event()? {
+            match event {
+                xcb::Event::X(XEvent::ConfigureNotify(_)) => changed = true,
+                xcb::Event::X(XEvent::ReparentNotify(_)) => {
+                    changed = true;
+                    reparented = true;
+                }
+                xcb::Event::Shape(shape::Event::Notify(_)) => changed = true,
+                xcb::Event::X(XEvent::DestroyNotify(event))
+                    if event.window() == self.impl_window.window =>
+                {
+                    self.destroyed = true;
+                }
+                _ => {}
+            }
+        }
+
+        if self.destroyed {
+            return Err(XCapError::new("The recorded window was destroyed"));
+        }
+
+        // 窗口管理器重启等情况下窗口会被 reparent 到新的 frame 窗口
+        if reparented {
+            self.select_events()?;
+        }
+
+        if changed {
+            self.rect = self.impl_window.outer_rect()?;
+            self.geometry = WindowGeometry::new(&self.impl_window)?;
+        }
+
+        Ok(self.rect)
+    }
+
+    /// 优先从合成 pixmap 中截取整个窗口，不会被遮挡，移出屏幕的部分也能截取；
+    /// 没有合成管理器时只能从 root 窗口截取屏幕上可见的部分
+    fn capture(&mut self, letterbox_color: [u8; 4]) -> XCapResult<RgbaImage> {
+        let rect = self.rect()?;
+        let inner_rect = self.geometry.inner_rect();
+
+        capture_window_area_with(
+            &self.impl_window,
+            &self.geometry,
+            rect.x - inner_rect.x,
+            rect.y - inner_rect.y,
+            rect.width,
+            rect.height,
+        )
+        .map(|window_capture| window_capture.image)
+        .or_else(|err| {
+            debug!("capture window area failed {err}");
+            capture_root_rect(rect, letterbox_color)
+        })
+    }
+}
+
+/// 窗口被销毁后所有请求都会返回 BadWindow，继续截取没有意义
+fn is_bad_window(err: &XCapError) -> bool {
+    matches!(
+        err,
+        XCapError::XcbError(xcb::Error::Protocol(xcb::ProtocolError::X(
+            xcb::x::Error::Window(_),
+            _
+        )))
+    )
+}
+
+/// smoothing 是视口在这段时间内剩余的距离比例，与帧率无关
+const CURSOR_SMOOTHING_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// 跟随鼠标的视口，视口中心按经过的时间向鼠标位置靠近
+struct CursorFollower {
+    monitor: ImplMonitor,
+    viewport: (u32, u32),
+    smoothing: f64,
+    center: Option<((f64, f64), Instant)>,
+}
+
+impl CursorFollower {
+    fn new(monitor: ImplMonitor, viewport: (u32, u32), smoothing: f64) -> Self {
+        CursorFollower {
+            monitor,
+            viewport,
+            smoothing,
+            center: None,
+        }
+    }
+
+    fn capture(&mut self) -> XCapResult<RgbaImage> {
+        let monitor_rect = Rect {
+            x: self.monitor.x()?,
+            y: self.monitor.y()?,
+            width: self.monitor.width()?,
+            height: self.monitor.height()?,
+        };
+
+        let (cursor_x, cursor_y) = ImplMonitor::cursor_position()?;
+        let cursor = (cursor_x as f64, cursor_y as f64);
+
+        let now = Instant::now();
+        let center = match self.center {
+            Some((center, last_update)) => smooth_towards(
+                center,
+                cursor,
+                self.smoothing,
+                now.duration_since(last_update),
+            ),
+            None => cursor,
+        };
+        self.center = Some((center, now));
+
+        let rect = cursor_viewport(center, monitor_rect, self.viewport);
+
+        self.monitor.capture_region(
+            (rect.x - monitor_rect.x) as u32,
+            (rect.y - monitor_rect.y) as u32,
+            rect.width,
+            rect.height,
+        )
+    }
+}
+
+/// 经过 elapsed 之后 center 向 target 靠近的位置
+fn smooth_towards(
+    center: (f64, f64),
+    target: (f64, f64),
+    smoothing: f64,
+    elapsed: Duration,
+) -> (f64, f64) {
+    let intervals = elapsed.as_secs_f64() / CURSOR_SMOOTHING_INTERVAL.as_secs_f64();
+    let factor = 1.0 - smoothing.powf(intervals);
+
+    (
+        center.0 + (target.0 - center.0) * factor,
+        center.1 + (target.1 - center.1) * factor,
+    )
+}
+
+/// 以 center 为中心的视口，视口不会超出显示器的范围
+fn cursor_viewport(center: (f64, f64), monitor_rect: Rect, viewport: (u32, u32)) -> Rect {
+    let width = viewport.0.min(monitor_rect.width);
+    let height = viewport.1.min(monitor_rect.height);
+
+    let max_x = monitor_rect.x + (monitor_rect.width - width) as i32;
+    let max_y = monitor_rect.y + (monitor_rect.height - height) as i32;
+
+    let x = ((center.0 - width as f64 / 2.0).round() as i32).clamp(monitor_rect.x, max_x);
+    let y = ((center.1 - height as f64 / 2.0).round() as i32).clamp(monitor_rect.y, max_y);
+
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+/// 从 root 窗口截取全局坐标下的区域，root 窗口覆盖所有显示器，因此跨显示器的窗口也能完整截取，
+/// 超出屏幕的部分使用 letterbox_color 填充
+fn capture_root_rect(rect: Rect, letterbox_color: [u8; 4]) -> XCapResult<RgbaImage> {
+    let screen_buf = get_current_screen_buf()?;
+
+    let left = rect.x.max(0);
+    let top = rect.y.max(0);
+    let right = (rect.x + rect.width as i32).min(screen_buf.width_in_pixels() as i32);
+    let bottom = (rect.y + rect.height as i32).min(screen_buf.height_in_pixels() as i32);
+
+    let mut image = RgbaImage::from_pixel(rect.width, rect.height, Rgba(letterbox_color));
+    if right > left && bottom > top {
+        let visible_image = xorg_capture(
+            screen_buf.root(),
+            left,
+            top,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        )?;
+
+        imageops::replace(
+            &mut image,
+            &visible_image,
+            (left - rect.x) as i64,
+            (top - rect.y) as i64,
+        );
+    }
+
+    Ok(image)
+}
 
 #[derive(Debug, Clone)]
 pub struct XorgVideoRecorder {
-    monitor: ImplMonitor,
+    source: RecorderSource,
+    options: VideoRecorderOptions,
     condition: Arc<Mutex<Condition>>,
     recorder_waker: Arc<RecorderWaker>,
 }
 
 impl XorgVideoRecorder {
-    pub fn new(monitor: ImplMonitor) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        source: RecorderSource,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
+        if let Some((width, height)) = options.follow_cursor {
+            // 只支持录制显示器时跟随鼠标，与其他平台一致
+            if matches!(source, RecorderSource::Window(_)) {
+                return Err(XCapError::NotSupported);
+            }
+
+            if width == 0 || height == 0 {
+                return Err(XCapError::new(format!(
+                    "Invalid cursor viewport size {width}x{height}"
+                )));
+            }
+        }
+
         let (sender, receiver) = mpsc::channel();
         let recorder = Self {
-            monitor,
+            source,
+            options,
             condition: Arc::new(Mutex::new(Condition::Init)),
             recorder_waker: Arc::new(RecorderWaker::new()),
         };
@@ -29,10 +312,33 @@ impl XorgVideoRecorder {
     }
 
     pub fn on_frame(&self, sender: Sender<Frame>) -> XCapResult<()> {
-        let monitor = self.monitor.clone();
+        let source = self.source.clone();
+        let options = self.options.clone();
         let cond = self.condition.clone();
         let recorder_waker = self.recorder_waker.clone();
 
+        let mut window_tracker = match &source {
+            RecorderSource::Window(impl_window) if options.follow_window => {
+                Some(WindowTracker::new(impl_window.clone())?)
+            }
+            _ => None,
+        };
+
+        let mut cursor_follower =
+            match (&source, options.follow_cursor) {
+                (RecorderSource::Monitor(impl_monitor), Some(viewport)) => Some(
+                    CursorFollower::new(impl_monitor.clone(), viewport, options.cursor_smoothing),
+                ),
+                _ => None,
+            };
+
+        // 跟随窗口时默认使用开始录制时的窗口大小作为输出大小，保证所有帧的尺寸一致
+        let output_size = options.output_size.or_else(|| {
+            window_tracker
+                .as_ref()
+                .map(|window_tracker| (window_tracker.rect.width, window_tracker.rect.height))
+        });
+
         thread::spawn(move || {
             loop {
                 if let Err(err) = recorder_waker.wait() {
@@ -40,7 +346,7 @@ impl XorgVideoRecorder {
                     break Err(err);
                 }
 
-                let cond = match cond.lock() {
+                let mut cond = match cond.lock() {
                     Ok(guard) => guard,
                     Err(e) => {
                         error!("Failed to lock running flag: {e:?}");
@@ -55,7 +361,30 @@ impl XorgVideoRecorder {
                     break Ok(());
                 }
 
-                match monitor.capture_image() {
+                // 每一帧都重新获取窗口大小，窗口大小改变时帧的尺寸也会随之改变
+                let capture_result = match (&source, window_tracker.as_mut()) {
+                    (RecorderSource::Window(_), Some(window_tracker)) => {
+                        window_tracker.capture(options.letterbox_color)
+                    }
+                    (RecorderSource::Window(window), None) => window.capture_image(),
+                    (RecorderSource::Monitor(monitor), _) => match cursor_follower.as_mut() {
+                        Some(cursor_follower) => cursor_follower.capture(),
+                        None => monitor.capture_image(),
+                    },
+                };
+
+                let capture_result = capture_result.map(|image| match output_size {
+                    Some((width, height)) => fit_frame(
+                        &image,
+                        width,
+                        height,
+                        options.scale_mode,
+                        options.letterbox_color,
+                    ),
+                    None => image,
+                });
+
+                match capture_result {
                     Ok(image) => {
                         let width = image.width();
                         let height = image.height();
@@ -68,6 +397,16 @@ impl XorgVideoRecorder {
                         }
                     }
                     Err(e) => {
+                        // 录制的窗口已经被销毁，停止录制并关闭帧通道
+                        if window_tracker.as_ref().is_some_and(|t| t.destroyed)
+                            || (matches!(source, RecorderSource::Window(_)) && is_bad_window(&e))
+                        {
+                            error!("Recorded window is gone, stopping the recorder: {e:?}");
+                            *cond = Condition::Stopped;
+                            drop(sender);
+                            break Err(e);
+                        }
+
                         error!("Failed to capture frame: {e:?}");
                         thread::sleep(Duration::from_millis(10));
                         continue;
@@ -136,3 +475,44 @@ impl Drop for XorgVideoRecorder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_viewport_clamps_to_monitor() {
+        let monitor_rect = Rect {
+            x: 1920,
+            y: 0,
+            width: 1280,
+            height: 720,
+        };
+
+        let rect = cursor_viewport((2560.0, 360.0), monitor_rect, (640, 360));
+        assert_eq!(
+            (rect.x, rect.y, rect.width, rect.height),
+            (2240, 180, 640, 360)
+        );
+
+        let rect = cursor_viewport((1925.0, 700.0), monitor_rect, (640, 360));
+        assert_eq!((rect.x, rect.y), (1920, 360));
+
+        let rect = cursor_viewport((0.0, 0.0), monitor_rect, (4000, 360));
+        assert_eq!(
+            (rect.x, rect.y, rect.width, rect.height),
+            (1920, 0, 1280, 360)
+        );
+    }
+
+    #[test]
+    fn smooth_towards_does_not_depend_on_frame_rate() {
+        let elapsed = CURSOR_SMOOTHING_INTERVAL * 2;
+        let once = smooth_towards((0.0, 0.0), (100.0, 50.0), 0.5, elapsed);
+        let half = smooth_towards((0.0, 0.0), (100.0, 50.0), 0.5, elapsed / 2);
+        let twice = smooth_towards(half, (100.0, 50.0), 0.5, elapsed / 2);
+
+        assert!((once.0 - 75.0).abs() < 1e-6);
+        assert!((once.0 - twice.0).abs() < 1e-6 && (once.1 - twice.1).abs() < 1e-6);
+    }
+}
@@ -6,14 +6,15 @@ use objc2_app_kit::NSScreen;
 use objc2_core_foundation::CGPoint;
 use objc2_core_graphics::{
     CGDirectDisplayID, CGDisplayBounds, CGDisplayCopyDisplayMode, CGDisplayIsActive,
-    CGDisplayIsBuiltin, CGDisplayIsMain, CGDisplayMode, CGDisplayRotation, CGError,
+    CGDisplayIsBuiltin, CGDisplayIsMain, CGDisplayMode, CGDisplayRotation, CGError, CGEvent,
     CGGetActiveDisplayList, CGGetDisplaysWithPoint, CGWindowListOption,
 };
 use objc2_foundation::{NSNumber, NSString};
 
 use crate::{
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    video_recorder::{Frame, FrameFitter, VideoRecorderOptions},
+    window::Rect,
 };
 
 use super::{capture::capture, impl_video_recorder::ImplVideoRecorder};
@@ -111,7 +112,7 @@ impl ImplMonitor {
         }
 
         if display_count == 0 {
-            return Err(XCapError::new("Monitor not found"));
+            return Err(XCapError::not_found("Monitor not found"));
         }
 
         if let Some(&display_id) = display_ids.first() {
@@ -120,9 +121,17 @@ impl ImplMonitor {
             }
             Ok(ImplMonitor::new(display_id))
         } else {
-            Err(XCapError::new("Monitor not found"))
+            Err(XCapError::not_found("Monitor not found"))
         }
     }
+
+    pub fn cursor_position() -> XCapResult<(i32, i32)> {
+        // 空事件的位置就是当前鼠标的位置，与 CGDisplayBounds 一样以主显示器左上角为原点
+        let event = CGEvent::new(None).ok_or(XCapError::new("CGEventCreate failed"))?;
+        let point = CGEvent::location(Some(&event));
+
+        Ok((point.x as i32, point.y as i32))
+    }
 }
 
 impl ImplMonitor {
@@ -161,6 +170,16 @@ impl ImplMonitor {
         Ok(cg_rect.size.height as u32)
     }
 
+    /// 显示器和窗口都使用以主显示器左上角为原点的点坐标
+    pub fn window_space_rect(&self) -> XCapResult<Rect> {
+        Ok(Rect {
+            x: self.x()?,
+            y: self.y()?,
+            width: self.width()?,
+            height: self.height()?,
+        })
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
         let rotation = unsafe { CGDisplayRotation(self.cg_direct_display_id) };
 
@@ -235,7 +254,28 @@ impl ImplMonitor {
         capture(cg_rect, CGWindowListOption::OptionAll, 0)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
-        ImplVideoRecorder::new(self.cg_direct_display_id)
+    pub fn video_recorder(
+        &self,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        // 录制显示器时忽略跟随窗口，portal 相关的选项也只在 Wayland 下生效
+        if options.follow_cursor.is_some() {
+            return Err(XCapError::NotSupported);
+        }
+
+        ImplVideoRecorder::new(self.cg_direct_display_id, FrameFitter::new(&options))
+    }
+
+    pub fn video_recorders(
+        options: VideoRecorderOptions,
+    ) -> XCapResult<Vec<(ImplMonitor, ImplVideoRecorder, Receiver<Frame>)>> {
+        ImplMonitor::all()?
+            .into_iter()
+            .map(|impl_monitor| {
+                let (recorder, receiver) = impl_monitor.video_recorder(options.clone())?;
+
+                Ok((impl_monitor, recorder, receiver))
+            })
+            .collect()
     }
 }
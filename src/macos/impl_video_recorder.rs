@@ -22,11 +22,15 @@ use objc2_core_video::{
 use objc2_foundation::{NSDictionary, NSNumber, NSObject, NSObjectProtocol, NSString};
 use scopeguard::defer;
 
-use crate::{XCapError, XCapResult, video_recorder::Frame};
+use crate::{
+    XCapError, XCapResult,
+    video_recorder::{Frame, FrameFitter},
+};
 
 #[derive(Debug, Clone)]
 struct DataOutputSampleBufferDelegateVars {
     tx: SyncSender<Frame>,
+    frame_fitter: FrameFitter,
 }
 
 impl DataOutputSampleBufferDelegateVars {
@@ -70,11 +74,11 @@ impl DataOutputSampleBufferDelegateVars {
                 bgra.swap(0, 2);
             }
 
-            let _ = self.tx.send(Frame {
+            let _ = self.tx.send(self.frame_fitter.fit(Frame {
                 width: width as u32,
                 height: height as u32,
                 raw: buffer,
-            });
+            }));
         }
     }
 }
@@ -102,8 +106,8 @@ define_class!(
 unsafe impl NSObjectProtocol for DataOutputSampleBufferDelegate {}
 
 impl DataOutputSampleBufferDelegate {
-    fn new(tx: SyncSender<Frame>) -> Retained<Self> {
-        let this = Self::alloc().set_ivars(DataOutputSampleBufferDelegateVars { tx });
+    fn new(tx: SyncSender<Frame>, frame_fitter: FrameFitter) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(DataOutputSampleBufferDelegateVars { tx, frame_fitter });
         unsafe { msg_send![super(this), init] }
     }
 }
@@ -117,7 +121,10 @@ pub struct ImplVideoRecorder {
 }
 
 impl ImplVideoRecorder {
-    pub fn new(cg_direct_display_id: CGDirectDisplayID) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        cg_direct_display_id: CGDirectDisplayID,
+        frame_fitter: FrameFitter,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         unsafe {
             let session = AVCaptureSession::new();
             let input = AVCaptureScreenInput::initWithDisplayID(
@@ -164,7 +171,7 @@ impl ImplVideoRecorder {
 
             let (tx, rx) = sync_channel(0);
 
-            let delegate = DataOutputSampleBufferDelegate::new(tx.clone());
+            let delegate = DataOutputSampleBufferDelegate::new(tx.clone(), frame_fitter);
 
             let sample_buffer_delegate = ProtocolObject::<
                 dyn AVCaptureVideoDataOutputSampleBufferDelegate,
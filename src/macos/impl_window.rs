@@ -1,9 +1,9 @@
-use std::ffi::c_void;
+use std::{ffi::c_void, sync::mpsc::Receiver};
 
 use image::RgbaImage;
 use objc2_app_kit::NSWorkspace;
 use objc2_core_foundation::{
-    CFBoolean, CFDictionary, CFNumber, CFNumberType, CFRetained, CFString, CGPoint, CGRect,
+    CFBoolean, CFDictionary, CFNumber, CFNumberType, CFRetained, CFString, CGPoint, CGRect, CGSize,
 };
 use objc2_core_graphics::{
     CGDisplayBounds, CGMainDisplayID, CGRectContainsPoint, CGRectIntersectsRect,
@@ -11,9 +11,18 @@ use objc2_core_graphics::{
 };
 use objc2_foundation::{NSNumber, NSString};
 
-use crate::{XCapError, error::XCapResult};
+use crate::{
+    XCapError,
+    error::XCapResult,
+    video_recorder::{Frame, VideoRecorderOptions},
+    window::{
+        AppInfo, FrameExtents, PidSource, Rect, WindowCapture, WindowCaptureOptions,
+        WindowListOptions, WindowState, WindowType, Workspace, to_thumbnail,
+    },
+    window_query::{WindowInfo, WindowInfoFields},
+};
 
-use super::{capture::capture, impl_monitor::ImplMonitor};
+use super::{capture::capture, impl_monitor::ImplMonitor, impl_video_recorder::ImplVideoRecorder};
 
 #[derive(Debug, Clone)]
 pub(crate) struct ImplWindow {
@@ -115,6 +124,24 @@ fn get_window_id(window_cf_dictionary: &CFDictionary) -> XCapResult<u32> {
     Ok(window_id as u32)
 }
 
+fn get_active_app_pid() -> Option<u32> {
+    let pid_key = NSString::from_str("NSApplicationProcessIdentifier");
+
+    unsafe {
+        let workspace = NSWorkspace::sharedWorkspace();
+
+        // activeApplication is deprecated, but the alternative, frontmostApplication,
+        // returns the application in focus when the process started while activeApplication
+        // returns a `NSDictionary` of application currently in focus, in real-time
+        let active_app_dictionary = workspace.activeApplication();
+
+        active_app_dictionary
+            .and_then(|dict| dict.valueForKey(&pid_key))
+            .and_then(|pid| pid.downcast::<NSNumber>().ok())
+            .map(|pid| pid.intValue() as u32)
+    }
+}
+
 pub fn get_window_cf_dictionary(window_id: u32) -> XCapResult<CFRetained<CFDictionary>> {
     unsafe {
         // CGWindowListCopyWindowInfo 返回窗口顺序为从顶层到最底层
@@ -148,7 +175,7 @@ pub fn get_window_cf_dictionary(window_id: u32) -> XCapResult<CFRetained<CFDicti
             }
         }
 
-        Err(XCapError::new("Window not found"))
+        Err(XCapError::not_found("Window not found"))
     }
 }
 
@@ -193,6 +220,128 @@ impl ImplWindow {
             Ok(impl_window)
         }
     }
+
+    pub fn all_with(options: WindowListOptions) -> XCapResult<Vec<ImplWindow>> {
+        if options != WindowListOptions::default() {
+            return Err(XCapError::NotSupported);
+        }
+
+        ImplWindow::all()
+    }
+
+    pub fn workspaces() -> XCapResult<Vec<Workspace>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn query_info(
+        impl_windows: &[ImplWindow],
+        fields: WindowInfoFields,
+    ) -> XCapResult<Vec<WindowInfo>> {
+        Ok(impl_windows
+            .iter()
+            .map(|impl_window| WindowInfo::fetch(impl_window, fields))
+            .collect())
+    }
+
+    pub fn from_id(id: u32) -> XCapResult<ImplWindow> {
+        get_window_cf_dictionary(id)?;
+
+        Ok(ImplWindow::new(id))
+    }
+
+    pub fn focused() -> XCapResult<ImplWindow> {
+        let active_app_pid =
+            get_active_app_pid().ok_or(XCapError::not_found("Not found focused window"))?;
+
+        // 窗口按照从顶层到最底层排序，活动应用的第一个窗口就是当前聚焦的窗口
+        ImplWindow::from_pid(active_app_pid)?
+            .into_iter()
+            .next()
+            .ok_or(XCapError::not_found("Not found focused window"))
+    }
+
+    pub fn from_pid(pid: u32) -> XCapResult<Vec<ImplWindow>> {
+        unsafe {
+            let mut impl_windows = Vec::new();
+
+            let cf_array = match CGWindowListCopyWindowInfo(
+                CGWindowListOption::OptionOnScreenOnly | CGWindowListOption::ExcludeDesktopElements,
+                0,
+            ) {
+                Some(cf_array) => cf_array,
+                None => return Ok(impl_windows),
+            };
+
+            for i in 0..cf_array.count() {
+                let window_cf_dictionary_ref = cf_array.value_at_index(i) as *const CFDictionary;
+
+                if window_cf_dictionary_ref.is_null() {
+                    continue;
+                }
+
+                let window_cf_dictionary = &*window_cf_dictionary_ref;
+
+                let window_id = match get_window_id(window_cf_dictionary) {
+                    Ok(window_id) => window_id,
+                    Err(_) => continue,
+                };
+
+                let window_pid =
+                    get_cf_number_i32_value(window_cf_dictionary, "kCGWindowOwnerPID")?;
+
+                if window_pid as u32 == pid {
+                    impl_windows.push(ImplWindow::new(window_id));
+                }
+            }
+
+            Ok(impl_windows)
+        }
+    }
+
+    pub fn from_point(x: i32, y: i32) -> XCapResult<ImplWindow> {
+        unsafe {
+            // CGWindowListCopyWindowInfo 返回窗口顺序为从顶层到最底层
+            // 第一个命中的就是最上层的窗口
+            let cf_array = match CGWindowListCopyWindowInfo(
+                CGWindowListOption::OptionOnScreenOnly | CGWindowListOption::ExcludeDesktopElements,
+                0,
+            ) {
+                Some(cf_array) => cf_array,
+                None => return Err(XCapError::new("Get window list failed")),
+            };
+
+            let cg_point = CGPoint {
+                x: x as f64,
+                y: y as f64,
+            };
+
+            for i in 0..cf_array.count() {
+                let window_cf_dictionary_ref = cf_array.value_at_index(i) as *const CFDictionary;
+
+                if window_cf_dictionary_ref.is_null() {
+                    continue;
+                }
+
+                let window_cf_dictionary = &*window_cf_dictionary_ref;
+
+                let window_id = match get_window_id(window_cf_dictionary) {
+                    Ok(window_id) => window_id,
+                    Err(_) => continue,
+                };
+
+                let cg_rect = match get_window_cg_rect(window_cf_dictionary) {
+                    Ok(cg_rect) => cg_rect,
+                    Err(_) => continue,
+                };
+
+                if CGRectContainsPoint(cg_rect, cg_point) {
+                    return Ok(ImplWindow::new(window_id));
+                }
+            }
+
+            Err(XCapError::not_found("Not found window"))
+        }
+    }
 }
 
 impl ImplWindow {
@@ -214,12 +363,29 @@ impl ImplWindow {
         get_cf_string_value(window_cf_dictionary.as_ref(), "kCGWindowOwnerName")
     }
 
+    pub fn pid_source(&self) -> XCapResult<PidSource> {
+        Ok(PidSource::Native)
+    }
+
+    pub fn app_info(&self) -> XCapResult<AppInfo> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn title(&self) -> XCapResult<String> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
 
         get_cf_string_value(window_cf_dictionary.as_ref(), "kCGWindowName")
     }
 
+    pub fn title_lossy(&self) -> XCapResult<String> {
+        // NSString 总是合法的 Unicode，不存在解码失败的情况
+        self.title()
+    }
+
+    pub fn icon(&self, _preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn current_monitor(&self) -> XCapResult<ImplMonitor> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
         let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
@@ -318,6 +484,22 @@ impl ImplWindow {
         Ok(cg_rect.size.height as u32)
     }
 
+    pub fn frame_extents(&self) -> XCapResult<FrameExtents> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn shadow_extents(&self) -> XCapResult<FrameExtents> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn outer_rect(&self) -> XCapResult<Rect> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn inner_rect(&self) -> XCapResult<Rect> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
         let is_on_screen = get_cf_bool_value(window_cf_dictionary.as_ref(), "kCGWindowIsOnscreen")?;
@@ -343,27 +525,55 @@ impl ImplWindow {
     }
 
     pub fn is_focused(&self) -> XCapResult<bool> {
-        let pid_key = NSString::from_str("NSApplicationProcessIdentifier");
+        let active_app_pid = get_active_app_pid();
 
-        unsafe {
-            let workspace = NSWorkspace::sharedWorkspace();
+        if active_app_pid == self.pid().ok() {
+            return Ok(true);
+        }
 
-            // activeApplication is deprecated, but the alternative, frontmostApplication,
-            // returns the application in focus when the process started while activeApplication
-            // returns a `NSDictionary` of application currently in focus, in real-time
-            let active_app_dictionary = workspace.activeApplication();
+        Ok(false)
+    }
 
-            let active_app_pid = active_app_dictionary
-                .and_then(|dict| dict.valueForKey(&pid_key))
-                .and_then(|pid| pid.downcast::<NSNumber>().ok())
-                .map(|pid| pid.intValue() as u32);
+    pub fn workspace(&self) -> XCapResult<Option<u32>> {
+        Err(XCapError::NotSupported)
+    }
 
-            if active_app_pid == self.pid().ok() {
-                return Ok(true);
-            }
+    pub fn is_override_redirect(&self) -> XCapResult<bool> {
+        Ok(false)
+    }
+
+    pub fn is_managed(&self) -> XCapResult<bool> {
+        Ok(true)
+    }
+
+    pub fn parent(&self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
 
-            Ok(false)
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn children(&self) -> XCapResult<Vec<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn state(&self) -> XCapResult<WindowState> {
+        let mut window_state = WindowState::empty();
+
+        if self.is_minimized()? {
+            window_state |= WindowState::Hidden;
         }
+
+        if self.is_maximized()? {
+            window_state |= WindowState::MaximizedHorz | WindowState::MaximizedVert;
+        }
+
+        Ok(window_state)
+    }
+
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        Err(XCapError::NotSupported)
     }
 
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
@@ -377,4 +587,73 @@ impl ImplWindow {
             self.window_id,
         )
     }
+
+    pub fn capture_image_with(&self, options: WindowCaptureOptions) -> XCapResult<RgbaImage> {
+        let window_capture = self.capture(options)?;
+
+        Ok(window_capture.image)
+    }
+
+    pub fn capture_region(&self, x: u32, y: u32, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+        let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+
+        // Validate region bounds
+        let window_width = cg_rect.size.width as u32;
+        let window_height = cg_rect.size.height as u32;
+
+        if width > window_width
+            || height > window_height
+            || x.checked_add(width)
+                .is_none_or(|right| right > window_width)
+            || y.checked_add(height)
+                .is_none_or(|bottom| bottom > window_height)
+        {
+            return Err(XCapError::InvalidCaptureRegion(format!(
+                "Region ({x}, {y}, {width}, {height}) is outside window bounds (0, 0, {window_width}, {window_height})"
+            )));
+        }
+
+        let cg_rect = CGRect {
+            origin: CGPoint {
+                x: cg_rect.origin.x + x as f64,
+                y: cg_rect.origin.y + y as f64,
+            },
+            size: CGSize {
+                width: width as f64,
+                height: height as f64,
+            },
+        };
+
+        capture(
+            cg_rect,
+            CGWindowListOption::OptionIncludingWindow,
+            self.window_id,
+        )
+    }
+
+    pub fn thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        let image = self.capture_image()?;
+
+        to_thumbnail(&image, max_width, max_height)
+    }
+
+    pub fn video_recorder(
+        &self,
+        _options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture(&self, options: WindowCaptureOptions) -> XCapResult<WindowCapture> {
+        if options != WindowCaptureOptions::default() {
+            return Err(XCapError::NotSupported);
+        }
+
+        // OptionIncludingWindow 只合成该窗口自身的内容，不受其他窗口遮挡
+        Ok(WindowCapture {
+            image: self.capture_image()?,
+            occlusion_safe: true,
+        })
+    }
 }
@@ -3,9 +3,19 @@ use std::sync::mpsc::Receiver;
 use image::RgbaImage;
 
 use crate::{
-    VideoRecorder, error::XCapResult, platform::impl_monitor::ImplMonitor, video_recorder::Frame,
+    VideoRecorder,
+    error::XCapResult,
+    platform::impl_monitor::ImplMonitor,
+    video_recorder::{Frame, VideoRecorderOptions},
 };
 
+/// The global cursor position, in the same coordinate space as [`Monitor::from_point`].
+///
+/// Returns [`crate::XCapError::NotSupported`] on Wayland, which has no global pointer position.
+pub fn cursor_position() -> XCapResult<(i32, i32)> {
+    ImplMonitor::cursor_position()
+}
+
 #[derive(Debug, Clone)]
 pub struct Monitor {
     pub(crate) impl_monitor: ImplMonitor,
@@ -32,6 +42,13 @@ impl Monitor {
 
         Ok(Monitor::new(impl_monitor))
     }
+
+    /// The monitor the cursor is currently on.
+    pub fn under_cursor() -> XCapResult<Monitor> {
+        let (x, y) = cursor_position()?;
+
+        Monitor::from_point(x, y)
+    }
 }
 
 impl Monitor {
@@ -93,10 +110,41 @@ impl Monitor {
     }
 
     pub fn video_recorder(&self) -> XCapResult<(VideoRecorder, Receiver<Frame>)> {
-        let (impl_video_recorder, sx) = self.impl_monitor.video_recorder()?;
+        self.video_recorder_with(VideoRecorderOptions::default())
+    }
+
+    /// Record the monitor with the given options, e.g. following the cursor.
+    pub fn video_recorder_with(
+        &self,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(VideoRecorder, Receiver<Frame>)> {
+        options.validate()?;
+        let (impl_video_recorder, sx) = self.impl_monitor.video_recorder(options)?;
 
         Ok((VideoRecorder::new(impl_video_recorder), sx))
     }
+
+    /// Record several monitors at once, one recorder per monitor.
+    ///
+    /// On Wayland the user picks the monitors in a single portal dialog,
+    /// elsewhere every monitor is recorded.
+    pub fn video_recorders(
+        options: VideoRecorderOptions,
+    ) -> XCapResult<Vec<(Monitor, VideoRecorder, Receiver<Frame>)>> {
+        options.validate()?;
+        let recorders = ImplMonitor::video_recorders(options)?
+            .into_iter()
+            .map(|(impl_monitor, impl_video_recorder, sx)| {
+                (
+                    Monitor::new(impl_monitor),
+                    VideoRecorder::new(impl_video_recorder),
+                    sx,
+                )
+            })
+            .collect();
+
+        Ok(recorders)
+    }
 }
 
 #[cfg(test)]
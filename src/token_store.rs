@@ -0,0 +1,251 @@
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Write as _},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use tempfile::NamedTempFile;
+
+use crate::{dir::data_dir, error::XCapResult};
+
+/// Identifies a restore token: the application it belongs to and the source it was granted for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenKey {
+    pub app_id: String,
+    /// The selected source, e.g. `monitor-DP-1` or `window`.
+    pub source: String,
+}
+
+impl TokenKey {
+    pub fn new<A: ToString, S: ToString>(app_id: A, source: S) -> Self {
+        TokenKey {
+            app_id: app_id.to_string(),
+            source: source.to_string(),
+        }
+    }
+}
+
+/// Default app id of [`TokenKey`]: the name of the running executable.
+pub(crate) fn default_app_id() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "xcap".to_string())
+}
+
+/// Storage for the restore tokens the screen cast portal hands out,
+/// see [`crate::VideoRecorderOptions::token_store`].
+///
+/// Implement it to keep the tokens in the application's own keyring or config.
+pub trait TokenStore: fmt::Debug + Send + Sync {
+    fn load(&self, key: &TokenKey) -> XCapResult<Option<String>>;
+
+    fn store(&self, key: &TokenKey, token: &str) -> XCapResult<()>;
+
+    /// Forget the token, e.g. after the portal rejected it. Removing a missing token is not an error.
+    fn remove(&self, key: &TokenKey) -> XCapResult<()>;
+}
+
+/// Stores every token in its own file, `<dir>/<app id>/<source>` with both names percent-encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTokenStore {
+    dir: PathBuf,
+}
+
+impl Default for FileTokenStore {
+    /// Stores the tokens in the crate's data directory.
+    fn default() -> Self {
+        FileTokenStore::new(data_dir().join("restore_tokens"))
+    }
+}
+
+/// Percent-encode every byte that is not safe in a file name, so distinct names never share a file.
+fn file_name(name: &str) -> String {
+    if name.is_empty() {
+        return "%".to_string();
+    }
+
+    let mut file_name = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => file_name.push(byte as char),
+            _ => {
+                let _ = write!(file_name, "%{byte:02X}");
+            }
+        }
+    }
+
+    file_name
+}
+
+impl FileTokenStore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        FileTokenStore {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &TokenKey) -> PathBuf {
+        self.dir
+            .join(file_name(&key.app_id))
+            .join(file_name(&key.source))
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self, key: &TokenKey) -> XCapResult<Option<String>> {
+        let token = match fs::read_to_string(self.path(key)) {
+            Ok(token) => token,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let token = token.trim();
+
+        Ok((!token.is_empty()).then(|| token.to_string()))
+    }
+
+    /// Writes a temporary file and renames it over the old token,
+    /// so a failed write never leaves a truncated token behind.
+    fn store(&self, key: &TokenKey, token: &str) -> XCapResult<()> {
+        let path = self.path(key);
+        let dir = path.parent().unwrap_or(&self.dir);
+        fs::create_dir_all(dir)?;
+
+        // The temporary file gets a unique name, so concurrent writers never share it,
+        // and it is deleted again when persisting fails.
+        let mut tmp_file = NamedTempFile::new_in(dir)?;
+        tmp_file.write_all(token.as_bytes())?;
+        tmp_file.persist(&path).map_err(|err| err.error)?;
+
+        Ok(())
+    }
+
+    fn remove(&self, key: &TokenKey) -> XCapResult<()> {
+        match fs::remove_file(self.path(key)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps the tokens in memory, so permissions are only remembered while the process runs.
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    tokens: Mutex<HashMap<TokenKey, String>>,
+}
+
+impl MemoryTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn load(&self, key: &TokenKey) -> XCapResult<Option<String>> {
+        Ok(self.tokens.lock()?.get(key).cloned())
+    }
+
+    fn store(&self, key: &TokenKey, token: &str) -> XCapResult<()> {
+        self.tokens.lock()?.insert(key.clone(), token.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &TokenKey) -> XCapResult<()> {
+        self.tokens.lock()?.remove(key);
+        Ok(())
+    }
+}
+
+/// A [`TokenStore`] shared between recorder options, compared by identity.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedTokenStore(pub Arc<dyn TokenStore>);
+
+impl PartialEq for SharedTokenStore {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_token_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileTokenStore::new(dir.path());
+        let monitor = TokenKey::new("kiosk", "monitor-DP-1");
+        let window = TokenKey::new("kiosk", "window");
+
+        assert_eq!(store.load(&monitor).unwrap(), None);
+        store.store(&monitor, "token-1").unwrap();
+        store.store(&window, "token-2").unwrap();
+        store.store(&monitor, "token-3").unwrap();
+        assert_eq!(store.load(&monitor).unwrap().as_deref(), Some("token-3"));
+        assert_eq!(store.load(&window).unwrap().as_deref(), Some("token-2"));
+
+        store.remove(&monitor).unwrap();
+        store.remove(&monitor).unwrap();
+        assert_eq!(store.load(&monitor).unwrap(), None);
+    }
+
+    #[test]
+    fn file_token_store_keeps_distinct_keys_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileTokenStore::new(dir.path());
+        let keys = [
+            TokenKey::new("kiosk", "monitor-DP.1"),
+            TokenKey::new("kiosk", "monitor-DP_1"),
+            TokenKey::new("kiosk", "monitor-DP%2E1"),
+            TokenKey::new("kiosk", ""),
+            TokenKey::new("kiosk", "_"),
+        ];
+
+        for (i, key) in keys.iter().enumerate() {
+            store.store(key, &format!("token-{i}")).unwrap();
+        }
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(store.load(key).unwrap(), Some(format!("token-{i}")));
+        }
+    }
+
+    #[test]
+    fn file_token_store_concurrent_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(FileTokenStore::new(dir.path()));
+        let key = TokenKey::new("kiosk", "window");
+
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                let key = key.clone();
+                std::thread::spawn(move || store.store(&key, &format!("token-{i}")).unwrap())
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let token = store.load(&key).unwrap().unwrap();
+        assert!(token.starts_with("token-"));
+        assert_eq!(fs::read_dir(dir.path().join("kiosk")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn memory_token_store_keys_by_app_and_source() {
+        let store = MemoryTokenStore::new();
+        let key = TokenKey::new("a", "window");
+        store.store(&key, "token").unwrap();
+
+        assert_eq!(store.load(&key).unwrap().as_deref(), Some("token"));
+        assert_eq!(store.load(&TokenKey::new("b", "window")).unwrap(), None);
+    }
+}
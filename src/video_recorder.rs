@@ -1,7 +1,18 @@
 use std::fmt::Display;
-use std::sync::{Condvar, Mutex};
+use std::sync::{
+    Arc, Condvar, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::Duration;
 
-use crate::{XCapResult, platform::impl_video_recorder::ImplVideoRecorder};
+use event_listener::Event;
+#[cfg(not(target_os = "android"))]
+use image::{Rgba, RgbaImage, imageops};
+
+use crate::{
+    TokenStore, XCapError, XCapResult, platform::impl_video_recorder::ImplVideoRecorder,
+    token_store::SharedTokenStore,
+};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(u8)]
@@ -42,6 +53,309 @@ impl Frame {
     }
 }
 
+/// How frames are fitted into the output size of a recorder.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum ScaleMode {
+    /// Keep the aspect ratio and fill the remaining area with the letterbox color.
+    #[default]
+    Letterbox,
+    /// Stretch the frame to the output size.
+    Stretch,
+}
+
+/// Whether the portal remembers the screen cast permission, so later recordings do not prompt the user.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum PersistMode {
+    /// Ask the user every time.
+    #[default]
+    None,
+    /// Remember the permission while the application is running.
+    Transient,
+    /// Remember the permission until it is revoked, across restarts.
+    Persistent,
+}
+
+/// How a Wayland recorder connects to PipeWire to receive the portal's stream.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum PipeWireRemote {
+    /// The restricted remote the portal opens for the session, which also works inside sandboxes like Flatpak.
+    #[default]
+    Portal,
+    /// The default PipeWire socket of the user session.
+    Default,
+}
+
+/// Aborts the portal requests of a recorder that is being created, e.g. when the user never
+/// answers the screen cast dialog. Clones share the same state.
+#[derive(Debug, Default, Clone)]
+pub struct CancelHandle {
+    state: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    event: Event,
+}
+
+impl CancelHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort the outstanding request, which then fails with [`crate::ErrorKind::Aborted`].
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.event.notify(usize::MAX);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once [`CancelHandle::cancel`] was called.
+    #[cfg(target_os = "linux")]
+    pub(crate) async fn cancelled(&self) {
+        while !self.is_cancelled() {
+            // 先注册监听再检查一次，避免错过监听前发出的通知
+            let listener = self.state.event.listen();
+            if self.is_cancelled() {
+                break;
+            }
+            listener.await;
+        }
+    }
+}
+
+impl PartialEq for CancelHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl Eq for CancelHandle {}
+
+/// Options for [`crate::Window::video_recorder_with`] and [`crate::Monitor::video_recorder_with`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoRecorderOptions {
+    pub(crate) follow_window: bool,
+    pub(crate) follow_cursor: Option<(u32, u32)>,
+    pub(crate) cursor_smoothing: f64,
+    pub(crate) output_size: Option<(u32, u32)>,
+    pub(crate) scale_mode: ScaleMode,
+    pub(crate) letterbox_color: [u8; 4],
+    pub(crate) portal_timeout: Option<Duration>,
+    pub(crate) cancel_handle: Option<CancelHandle>,
+    pub(crate) persist_mode: PersistMode,
+    pub(crate) token_store: Option<SharedTokenStore>,
+    pub(crate) app_id: Option<String>,
+    pub(crate) pipewire_remote: PipeWireRemote,
+}
+
+impl Default for VideoRecorderOptions {
+    fn default() -> Self {
+        VideoRecorderOptions {
+            follow_window: false,
+            follow_cursor: None,
+            cursor_smoothing: 0.0,
+            output_size: None,
+            scale_mode: ScaleMode::Letterbox,
+            letterbox_color: [0, 0, 0, 255],
+            portal_timeout: None,
+            cancel_handle: None,
+            persist_mode: PersistMode::None,
+            token_store: None,
+            app_id: None,
+            pipewire_remote: PipeWireRemote::Portal,
+        }
+    }
+}
+
+impl VideoRecorderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track the window while it is moved or resized, capturing it from whichever monitor it is on.
+    /// The output size defaults to the window size when recording starts.
+    pub fn follow_window(mut self, follow_window: bool) -> Self {
+        self.follow_window = follow_window;
+        self
+    }
+
+    /// Crop a `width` x `height` viewport around the pointer, clamped to the monitor bounds.
+    /// Only supported when recording a monitor on X11.
+    pub fn follow_cursor(mut self, width: u32, height: u32) -> Self {
+        self.follow_cursor = Some((width, height));
+        self
+    }
+
+    /// How slowly the viewport of [`VideoRecorderOptions::follow_cursor`] catches up with the pointer,
+    /// from `0.0` (jump to the pointer every frame) to close to `1.0` (barely move).
+    /// It is the share of the distance left after 1/60 of a second, so the speed does not depend on the frame rate.
+    pub fn cursor_smoothing(mut self, cursor_smoothing: f64) -> Self {
+        self.cursor_smoothing = cursor_smoothing.clamp(0.0, 0.99);
+        self
+    }
+
+    /// Emit every frame with this size. Both dimensions must be non-zero,
+    /// otherwise creating the recorder fails with [`crate::ErrorKind::InvalidCaptureRegion`].
+    pub fn output_size(mut self, width: u32, height: u32) -> Self {
+        self.output_size = Some((width, height));
+        self
+    }
+
+    pub fn scale_mode(mut self, scale_mode: ScaleMode) -> Self {
+        self.scale_mode = scale_mode;
+        self
+    }
+
+    /// RGBA color of the letterbox bars and of the area outside all monitors.
+    pub fn letterbox_color(mut self, letterbox_color: [u8; 4]) -> Self {
+        self.letterbox_color = letterbox_color;
+        self
+    }
+
+    /// Give up on a screen cast portal request (e.g. the screen cast dialog) after `timeout`,
+    /// failing with [`crate::ErrorKind::Timeout`]. Waits forever by default.
+    ///
+    /// Only covers the requests of the recorder, screenshots taken through the Screenshot
+    /// portal still wait for the answer.
+    pub fn portal_timeout(mut self, timeout: Duration) -> Self {
+        self.portal_timeout = Some(timeout);
+        self
+    }
+
+    /// Abort the screen cast portal requests made while creating the recorder when
+    /// `cancel_handle` is cancelled. Screenshots taken through the portal are not affected.
+    pub fn cancel_handle(mut self, cancel_handle: CancelHandle) -> Self {
+        self.cancel_handle = Some(cancel_handle);
+        self
+    }
+
+    /// Keep the screen cast permission granted in the portal dialog, storing the restore token
+    /// the portal hands out so the next recording starts without prompting.
+    pub fn persist_mode(mut self, persist_mode: PersistMode) -> Self {
+        self.persist_mode = persist_mode;
+        self
+    }
+
+    /// Where the restore tokens of [`PersistMode::Persistent`] are kept,
+    /// a [`crate::FileTokenStore`] in the crate's data directory by default.
+    /// Tokens of [`PersistMode::Transient`] always stay in memory.
+    pub fn token_store(mut self, token_store: Arc<dyn TokenStore>) -> Self {
+        self.token_store = Some(SharedTokenStore(token_store));
+        self
+    }
+
+    /// The application the restore tokens are stored for, the executable name by default.
+    pub fn app_id(mut self, app_id: &str) -> Self {
+        self.app_id = Some(app_id.to_string());
+        self
+    }
+
+    /// How Wayland recorders connect to PipeWire. [`PipeWireRemote::Portal`], the default, opens
+    /// a separate remote through the portal for every recorder, which also works in sandboxes like
+    /// Flatpak. [`PipeWireRemote::Default`] connects to the session's PipeWire socket instead.
+    pub fn pipewire_remote(mut self, pipewire_remote: PipeWireRemote) -> Self {
+        self.pipewire_remote = pipewire_remote;
+        self
+    }
+
+    /// Reject options no recorder can honor, checked before creating a recorder.
+    pub(crate) fn validate(&self) -> XCapResult<()> {
+        if let Some((width, height)) = self.output_size
+            && (width == 0 || height == 0)
+        {
+            return Err(XCapError::InvalidCaptureRegion(format!(
+                "Output size {width}x{height} must not be empty"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Fit an image into `width` x `height` according to `scale_mode`.
+#[cfg(not(target_os = "android"))]
+pub(crate) fn fit_frame(
+    image: &RgbaImage,
+    width: u32,
+    height: u32,
+    scale_mode: ScaleMode,
+    letterbox_color: [u8; 4],
+) -> RgbaImage {
+    if image.dimensions() == (width, height) {
+        return image.clone();
+    }
+
+    match scale_mode {
+        ScaleMode::Stretch => {
+            imageops::resize(image, width, height, imageops::FilterType::Triangle)
+        }
+        ScaleMode::Letterbox => {
+            let scale =
+                (width as f64 / image.width() as f64).min(height as f64 / image.height() as f64);
+            let scaled_width = ((image.width() as f64 * scale).round() as u32).clamp(1, width);
+            let scaled_height = ((image.height() as f64 * scale).round() as u32).clamp(1, height);
+
+            let scaled = imageops::resize(
+                image,
+                scaled_width,
+                scaled_height,
+                imageops::FilterType::Triangle,
+            );
+
+            let mut frame = RgbaImage::from_pixel(width, height, Rgba(letterbox_color));
+            imageops::replace(
+                &mut frame,
+                &scaled,
+                ((width - scaled_width) / 2) as i64,
+                ((height - scaled_height) / 2) as i64,
+            );
+
+            frame
+        }
+    }
+}
+
+/// Applies the output size of [`VideoRecorderOptions`] to the frames of backends that capture at the source size.
+#[cfg(not(target_os = "android"))]
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct FrameFitter {
+    output_size: Option<(u32, u32)>,
+    scale_mode: ScaleMode,
+    letterbox_color: [u8; 4],
+}
+
+#[cfg(not(target_os = "android"))]
+impl FrameFitter {
+    pub fn new(options: &VideoRecorderOptions) -> Self {
+        FrameFitter {
+            output_size: options.output_size,
+            scale_mode: options.scale_mode,
+            letterbox_color: options.letterbox_color,
+        }
+    }
+
+    pub fn fit(&self, frame: Frame) -> Frame {
+        let Some((width, height)) = self.output_size else {
+            return frame;
+        };
+        if (frame.width, frame.height) == (width, height)
+            || frame.raw.len() < frame.width as usize * frame.height as usize * 4
+        {
+            return frame;
+        }
+
+        let image = RgbaImage::from_raw(frame.width, frame.height, frame.raw)
+            .expect("Frame buffer was checked to fit the frame size");
+        let image = fit_frame(&image, width, height, self.scale_mode, self.letterbox_color);
+
+        Frame::new(width, height, image.into_raw())
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub(crate) struct RecorderWaker {
@@ -196,3 +510,71 @@ impl VideoRecorder {
         self.impl_video_recorder.stop()
     }
 }
+
+#[cfg(all(test, not(target_os = "android")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_frame_letterbox_centers_image() {
+        let image = RgbaImage::from_pixel(4, 2, Rgba([255, 255, 255, 255]));
+        let frame = fit_frame(&image, 4, 4, ScaleMode::Letterbox, [0, 0, 0, 255]);
+
+        assert_eq!(frame.dimensions(), (4, 4));
+        assert_eq!(frame.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(0, 1).0, [255, 255, 255, 255]);
+        assert_eq!(frame.get_pixel(3, 2).0, [255, 255, 255, 255]);
+        assert_eq!(frame.get_pixel(3, 3).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn fit_frame_stretch_fills_output() {
+        let image = RgbaImage::from_pixel(4, 2, Rgba([255, 255, 255, 255]));
+        let frame = fit_frame(&image, 4, 4, ScaleMode::Stretch, [0, 0, 0, 255]);
+
+        assert_eq!(frame.dimensions(), (4, 4));
+        assert_eq!(frame.get_pixel(0, 0).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn frame_fitter_applies_output_size() {
+        let frame = Frame::new(4, 2, vec![255; 4 * 2 * 4]);
+
+        let unchanged = FrameFitter::new(&VideoRecorderOptions::default()).fit(frame.clone());
+        assert_eq!((unchanged.width, unchanged.height), (4, 2));
+
+        let options = VideoRecorderOptions::default().output_size(8, 8);
+        let fitted = FrameFitter::new(&options).fit(frame);
+        assert_eq!((fitted.width, fitted.height), (8, 8));
+        assert_eq!(fitted.raw.len(), 8 * 8 * 4);
+    }
+
+    #[test]
+    fn options_reject_empty_output_size() {
+        assert!(VideoRecorderOptions::default().validate().is_ok());
+        assert!(
+            VideoRecorderOptions::default()
+                .output_size(8, 8)
+                .validate()
+                .is_ok()
+        );
+
+        let err = VideoRecorderOptions::default()
+            .output_size(0, 8)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidCaptureRegion);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cancel_handle_wakes_waiting_request() {
+        let cancel_handle = CancelHandle::new();
+        let clone = cancel_handle.clone();
+        let thread = std::thread::spawn(move || futures_lite::future::block_on(clone.cancelled()));
+
+        cancel_handle.cancel();
+        thread.join().unwrap();
+        assert!(cancel_handle.is_cancelled());
+    }
+}
@@ -1,6 +1,192 @@
-use image::RgbaImage;
+use std::{path::PathBuf, sync::mpsc::Receiver};
 
-use crate::{Monitor, error::XCapResult, platform::impl_window::ImplWindow};
+use bitflags::bitflags;
+use image::{RgbaImage, imageops};
+
+use crate::{
+    Monitor,
+    error::{XCapError, XCapResult},
+    platform::impl_window::ImplWindow,
+    video_recorder::{Frame, VideoRecorder, VideoRecorderOptions},
+};
+
+bitflags! {
+    /// The window state, modeled after `_NET_WM_STATE` of the EWMH spec.
+    /// https://specifications.freedesktop.org/wm-spec/1.5/ar01s05.html#id-1.6.8
+    #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+    pub struct WindowState: u32 {
+        const Hidden = 1 << 0;
+        const MaximizedHorz = 1 << 1;
+        const MaximizedVert = 1 << 2;
+        const Fullscreen = 1 << 3;
+        const Above = 1 << 4;
+        const Below = 1 << 5;
+        const Sticky = 1 << 6;
+        const Shaded = 1 << 7;
+        const SkipTaskbar = 1 << 8;
+        const SkipPager = 1 << 9;
+        const Modal = 1 << 10;
+        const DemandsAttention = 1 << 11;
+    }
+}
+
+impl WindowState {
+    /// Whether the window is maximized both horizontally and vertically.
+    pub fn is_maximized(&self) -> bool {
+        self.contains(WindowState::MaximizedHorz | WindowState::MaximizedVert)
+    }
+}
+
+/// The functional type of the window, modeled after `_NET_WM_WINDOW_TYPE` of the EWMH spec.
+/// https://specifications.freedesktop.org/wm-spec/1.5/ar01s05.html#id-1.6.7
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum WindowType {
+    Normal,
+    Desktop,
+    Dock,
+    Toolbar,
+    Menu,
+    Utility,
+    Splash,
+    Dialog,
+    DropdownMenu,
+    PopupMenu,
+    Tooltip,
+    Notification,
+    Combo,
+    Dnd,
+}
+
+/// Where the process id of a window was obtained from.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum PidSource {
+    /// Queried from the platform window API, e.g. `GetWindowThreadProcessId` on Windows.
+    Native,
+    /// The `_NET_WM_PID` property set by the X11 client itself.
+    NetWmPid,
+    /// The X-Resource extension, resolved by the X server from the client connection.
+    XRes,
+}
+
+/// The application a window belongs to.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AppInfo {
+    /// Human readable application name, e.g. `IntelliJ IDEA`.
+    pub name: String,
+    /// Desktop file id, e.g. `jetbrains-idea.desktop`.
+    pub desktop_id: Option<String>,
+    /// Executable path of the owning process.
+    pub executable: Option<PathBuf>,
+    /// Command line of the owning process.
+    pub command_line: Vec<String>,
+}
+
+/// Sizes of the borders around a window, in pixels.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct FrameExtents {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+/// A rectangle in screen coordinates.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Options for [`Window::capture_image_with`].
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct WindowCaptureOptions {
+    /// Include the title bar and borders drawn by the window manager.
+    pub include_frame: bool,
+    /// Exclude the shadow drawn by the client itself around client-side decorations.
+    pub exclude_shadow: bool,
+}
+
+/// Options for [`Window::all_with`].
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct WindowListOptions {
+    /// Also list windows the window manager does not manage, such as menus, tooltips, popups
+    /// and notifications (override-redirect windows on X11).
+    pub include_unmanaged: bool,
+    /// Only list windows from these workspaces.
+    pub workspace: WorkspaceFilter,
+}
+
+/// Which workspaces [`Window::all_with`] lists windows from.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum WorkspaceFilter {
+    /// Windows on every workspace.
+    #[default]
+    All,
+    /// Windows that appear on the current workspace, including sticky windows.
+    Current,
+    /// Windows that appear on every workspace.
+    Sticky,
+}
+
+/// A virtual desktop, modeled after `_NET_NUMBER_OF_DESKTOPS` of the EWMH spec.
+/// https://specifications.freedesktop.org/wm-spec/1.5/ar01s03.html#id-1.4.5
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Workspace {
+    /// The workspace index, as returned by [`Window::workspace`].
+    pub id: u32,
+    pub name: String,
+    pub is_current: bool,
+    /// The viewport position and size of the workspace.
+    pub rect: Rect,
+}
+
+/// List the workspaces of the window manager.
+pub fn workspaces() -> XCapResult<Vec<Workspace>> {
+    ImplWindow::workspaces()
+}
+
+/// The result of [`Window::capture`].
+#[derive(Debug, Clone)]
+pub struct WindowCapture {
+    pub image: RgbaImage,
+    /// Whether the image holds the window's own content, unaffected by windows overlapping it.
+    pub occlusion_safe: bool,
+}
+
+/// The largest size fitting in `max_width` x `max_height` that keeps the aspect ratio, never upscaling.
+fn thumbnail_size(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    if width <= max_width && height <= max_height {
+        return (width, height);
+    }
+
+    let scale = (max_width as f64 / width as f64).min(max_height as f64 / height as f64);
+
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+pub(crate) fn to_thumbnail(
+    image: &RgbaImage,
+    max_width: u32,
+    max_height: u32,
+) -> XCapResult<RgbaImage> {
+    if max_width == 0 || max_height == 0 {
+        return Err(XCapError::new(format!(
+            "Invalid thumbnail size {max_width}x{max_height}"
+        )));
+    }
+
+    let (width, height) = thumbnail_size(image.width(), image.height(), max_width, max_height);
+    if (width, height) == image.dimensions() {
+        return Ok(image.clone());
+    }
+
+    Ok(imageops::thumbnail(image, width, height))
+}
 
 #[derive(Debug, Clone)]
 pub struct Window {
@@ -23,6 +209,47 @@ impl Window {
 
         Ok(windows)
     }
+
+    /// List windows with the given options, sorted by z coordinate.
+    pub fn all_with(options: WindowListOptions) -> XCapResult<Vec<Window>> {
+        let windows = ImplWindow::all_with(options)?
+            .into_iter()
+            .map(Window::new)
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// Get the window with the given id.
+    pub fn from_id(id: u32) -> XCapResult<Window> {
+        let impl_window = ImplWindow::from_id(id)?;
+
+        Ok(Window::new(impl_window))
+    }
+
+    /// Get the currently focused window.
+    pub fn focused() -> XCapResult<Window> {
+        let impl_window = ImplWindow::focused()?;
+
+        Ok(Window::new(impl_window))
+    }
+
+    /// List all windows owned by the given process id, sorted by z coordinate.
+    pub fn from_pid(pid: u32) -> XCapResult<Vec<Window>> {
+        let windows = ImplWindow::from_pid(pid)?
+            .into_iter()
+            .map(Window::new)
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// Get the topmost visible window under the given global coordinate.
+    pub fn from_point(x: i32, y: i32) -> XCapResult<Window> {
+        let impl_window = ImplWindow::from_point(x, y)?;
+
+        Ok(Window::new(impl_window))
+    }
 }
 
 impl Window {
@@ -34,14 +261,38 @@ impl Window {
     pub fn pid(&self) -> XCapResult<u32> {
         self.impl_window.pid()
     }
+    /// Where the window process id was obtained from
+    pub fn pid_source(&self) -> XCapResult<PidSource> {
+        self.impl_window.pid_source()
+    }
     /// The window app name
     pub fn app_name(&self) -> XCapResult<String> {
         self.impl_window.app_name()
     }
-    /// The window title
+    /// The application the window belongs to, resolved against installed desktop entries.
+    pub fn app_info(&self) -> XCapResult<AppInfo> {
+        self.impl_window.app_info()
+    }
+    /// The window title.
+    ///
+    /// On Linux, fails for `COMPOUND_TEXT` titles in character sets that cannot be decoded,
+    /// use [`Window::title_lossy`] to get them with U+FFFD instead.
     pub fn title(&self) -> XCapResult<String> {
         self.impl_window.title()
     }
+    /// The window title, replacing characters that cannot be decoded with U+FFFD instead of failing
+    pub fn title_lossy(&self) -> XCapResult<String> {
+        self.impl_window.title_lossy()
+    }
+    /// The window icon, choosing the available size closest to `preferred_size`.
+    /// Returns `None` if the window has no icon.
+    ///
+    /// On Linux, windows without `_NET_WM_ICON` fall back to the PNG icon of their desktop entry,
+    /// looked up in the `<size>x<size>/apps` directories of the installed icon themes and in
+    /// `/usr/share/pixmaps`. SVG-only icons are not found.
+    pub fn icon(&self, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        self.impl_window.icon(preferred_size)
+    }
     /// The window current monitor
     pub fn current_monitor(&self) -> XCapResult<Monitor> {
         Ok(Monitor::new(self.impl_window.current_monitor()?))
@@ -66,6 +317,22 @@ impl Window {
     pub fn height(&self) -> XCapResult<u32> {
         self.impl_window.height()
     }
+    /// The size of the title bar and borders drawn by the window manager around the window.
+    pub fn frame_extents(&self) -> XCapResult<FrameExtents> {
+        self.impl_window.frame_extents()
+    }
+    /// The size of the shadow drawn by the client itself, inside the window area.
+    pub fn shadow_extents(&self) -> XCapResult<FrameExtents> {
+        self.impl_window.shadow_extents()
+    }
+    /// The window rect including the frame drawn by the window manager.
+    pub fn outer_rect(&self) -> XCapResult<Rect> {
+        self.impl_window.outer_rect()
+    }
+    /// The window rect excluding the frame, same as `x`, `y`, `width` and `height`.
+    pub fn inner_rect(&self) -> XCapResult<Rect> {
+        self.impl_window.inner_rect()
+    }
     /// The window is minimized.
     pub fn is_minimized(&self) -> XCapResult<bool> {
         self.impl_window.is_minimized()
@@ -78,10 +345,107 @@ impl Window {
     pub fn is_focused(&self) -> XCapResult<bool> {
         self.impl_window.is_focused()
     }
+    /// The window bypasses the window manager, like menus and tooltips (override-redirect on X11).
+    pub fn is_override_redirect(&self) -> XCapResult<bool> {
+        self.impl_window.is_override_redirect()
+    }
+    /// The window is managed by the window manager.
+    pub fn is_managed(&self) -> XCapResult<bool> {
+        self.impl_window.is_managed()
+    }
+    /// The parent window, `None` for top-level windows.
+    pub fn parent(&self) -> XCapResult<Option<Window>> {
+        let parent = self.impl_window.parent()?;
+
+        Ok(parent.map(Window::new))
+    }
+    /// The window this window is transient for, e.g. the main window of a dialog.
+    pub fn transient_for(&self) -> XCapResult<Option<Window>> {
+        let transient_for = self.impl_window.transient_for()?;
+
+        Ok(transient_for.map(Window::new))
+    }
+    /// The direct child windows in the window system's hierarchy, sorted by z coordinate.
+    /// These are sub-windows nested inside this one (e.g. the client window inside a frame window),
+    /// not the dialogs it owns: find those through [`Window::transient_for`] of [`Window::all`].
+    pub fn children(&self) -> XCapResult<Vec<Window>> {
+        let children = self
+            .impl_window
+            .children()?
+            .into_iter()
+            .map(Window::new)
+            .collect();
+
+        Ok(children)
+    }
+    /// The index of the workspace the window is on.
+    /// Returns `None` if the window appears on every workspace or is not assigned to one.
+    pub fn workspace(&self) -> XCapResult<Option<u32>> {
+        self.impl_window.workspace()
+    }
+    /// The window state flags.
+    pub fn state(&self) -> XCapResult<WindowState> {
+        self.impl_window.state()
+    }
+    /// The window type, e.g. normal, dialog or dock.
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        self.impl_window.window_type()
+    }
 }
 
 impl Window {
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         self.impl_window.capture_image()
     }
+
+    /// Capture the window with the given options, e.g. including the window manager frame.
+    pub fn capture_image_with(&self, options: WindowCaptureOptions) -> XCapResult<RgbaImage> {
+        self.impl_window.capture_image_with(options)
+    }
+
+    /// Capture a region of the window, `x` and `y` are relative to the window.
+    pub fn capture_region(&self, x: u32, y: u32, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        self.impl_window.capture_region(x, y, width, height)
+    }
+
+    /// Capture the window downscaled to fit in `max_width` x `max_height`, keeping the aspect ratio.
+    pub fn thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        self.impl_window.thumbnail(max_width, max_height)
+    }
+
+    /// Record the window. Frames follow the window size when it is resized.
+    ///
+    /// On Wayland the portal asks the user to pick the window to record.
+    pub fn video_recorder(&self) -> XCapResult<(VideoRecorder, Receiver<Frame>)> {
+        self.video_recorder_with(VideoRecorderOptions::default())
+    }
+
+    /// Record the window with the given options, e.g. following the window across monitors.
+    pub fn video_recorder_with(
+        &self,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(VideoRecorder, Receiver<Frame>)> {
+        options.validate()?;
+        let (impl_video_recorder, sx) = self.impl_window.video_recorder(options)?;
+
+        Ok((VideoRecorder::new(impl_video_recorder), sx))
+    }
+
+    /// Capture the window with the given options, also reporting whether the capture is occlusion-safe.
+    pub fn capture(&self, options: WindowCaptureOptions) -> XCapResult<WindowCapture> {
+        self.impl_window.capture(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thumbnail_size_keeps_aspect_ratio() {
+        assert_eq!(thumbnail_size(1920, 1080, 320, 320), (320, 180));
+        assert_eq!(thumbnail_size(1080, 1920, 320, 320), (180, 320));
+        assert_eq!(thumbnail_size(200, 100, 320, 320), (200, 100));
+        assert_eq!(thumbnail_size(4000, 1, 100, 100), (100, 1));
+    }
 }
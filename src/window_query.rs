@@ -0,0 +1,489 @@
+use bitflags::bitflags;
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::{
+    Monitor,
+    error::XCapResult,
+    platform::impl_window::ImplWindow,
+    window::{Rect, Window, WindowListOptions, WindowType},
+};
+
+bitflags! {
+    /// The [`WindowInfo`] fields a [`WindowQuery`] needs, so platforms only fetch those.
+    #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+    pub(crate) struct WindowInfoFields: u8 {
+        const AppName = 1 << 0;
+        const Title = 1 << 1;
+        const Pid = 1 << 2;
+        const Rect = 1 << 3;
+        const State = 1 << 4;
+        const WindowType = 1 << 5;
+        const Workspace = 1 << 6;
+    }
+}
+
+/// Window metadata fetched for all windows at once by [`WindowQuery`], `None` where it could not be read.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct WindowInfo {
+    pub app_name: Option<String>,
+    pub title: Option<String>,
+    pub pid: Option<u32>,
+    pub rect: Option<Rect>,
+    pub is_minimized: Option<bool>,
+    pub is_visible: Option<bool>,
+    pub window_type: Option<WindowType>,
+    pub workspace: Option<Option<u32>>,
+}
+
+impl WindowInfo {
+    /// Fetch the requested metadata of a single window through the regular getters,
+    /// for platforms where the queries cannot be batched.
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn fetch(impl_window: &ImplWindow, fields: WindowInfoFields) -> WindowInfo {
+        let mut window_info = WindowInfo::default();
+
+        if fields.contains(WindowInfoFields::AppName) {
+            window_info.app_name = impl_window.app_name().ok();
+        }
+        if fields.contains(WindowInfoFields::Title) {
+            window_info.title = impl_window.title().ok();
+        }
+        if fields.contains(WindowInfoFields::Pid) {
+            window_info.pid = impl_window.pid().ok();
+        }
+        if fields.contains(WindowInfoFields::Rect) {
+            window_info.rect = match (
+                impl_window.x(),
+                impl_window.y(),
+                impl_window.width(),
+                impl_window.height(),
+            ) {
+                (Ok(x), Ok(y), Ok(width), Ok(height)) => Some(Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                }),
+                _ => None,
+            };
+        }
+        if fields.contains(WindowInfoFields::State) {
+            window_info.is_minimized = impl_window.is_minimized().ok();
+            window_info.is_visible = window_info.is_minimized.map(|is_minimized| !is_minimized);
+        }
+        if fields.contains(WindowInfoFields::WindowType) {
+            window_info.window_type = impl_window.window_type().ok();
+        }
+        if fields.contains(WindowInfoFields::Workspace) {
+            window_info.workspace = impl_window.workspace().ok();
+        }
+
+        window_info
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TextFilter {
+    Contains(String),
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+}
+
+impl TextFilter {
+    fn is_match(&self, text: &str) -> bool {
+        match self {
+            TextFilter::Contains(pattern) => text.contains(pattern.as_str()),
+            #[cfg(feature = "regex")]
+            TextFilter::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+fn overlap_area(a: Rect, b: Rect) -> i64 {
+    let left = a.x.max(b.x) as i64;
+    let top = a.y.max(b.y) as i64;
+    let right = (a.x as i64 + a.width as i64).min(b.x as i64 + b.width as i64);
+    let bottom = (a.y as i64 + a.height as i64).min(b.y as i64 + b.height as i64);
+
+    (right - left).max(0) * (bottom - top).max(0)
+}
+
+/// Lists windows matching all of the given filters.
+///
+/// The metadata the filters need is fetched once for all windows,
+/// batching the requests where the platform allows it.
+///
+/// ```no_run
+/// use xcap::{WindowQuery, WindowType};
+///
+/// let windows = WindowQuery::new()
+///     .app_name("firefox")
+///     .window_type(WindowType::Normal)
+///     .minimized(false)
+///     .windows()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WindowQuery {
+    list_options: WindowListOptions,
+    app_name: Option<TextFilter>,
+    title: Option<TextFilter>,
+    pid: Option<u32>,
+    monitor: Option<Monitor>,
+    minimized: Option<bool>,
+    visible: Option<bool>,
+    window_type: Option<WindowType>,
+    min_size: Option<(u32, u32)>,
+    workspace: Option<u32>,
+    topmost_first: bool,
+}
+
+impl Default for WindowQuery {
+    fn default() -> Self {
+        WindowQuery {
+            list_options: WindowListOptions::default(),
+            app_name: None,
+            title: None,
+            pid: None,
+            monitor: None,
+            minimized: None,
+            visible: None,
+            window_type: None,
+            min_size: None,
+            workspace: None,
+            topmost_first: true,
+        }
+    }
+}
+
+impl WindowQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The options windows are listed with, see [`Window::all_with`].
+    pub fn list_options(mut self, list_options: WindowListOptions) -> Self {
+        self.list_options = list_options;
+        self
+    }
+
+    /// Only windows whose app name contains `app_name`.
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.app_name = Some(TextFilter::Contains(app_name.to_string()));
+        self
+    }
+
+    /// Only windows whose app name matches `regex`, with the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn app_name_regex(mut self, regex: Regex) -> Self {
+        self.app_name = Some(TextFilter::Regex(regex));
+        self
+    }
+
+    /// Only windows whose title contains `title`.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(TextFilter::Contains(title.to_string()));
+        self
+    }
+
+    /// Only windows whose title matches `regex`, with the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn title_regex(mut self, regex: Regex) -> Self {
+        self.title = Some(TextFilter::Regex(regex));
+        self
+    }
+
+    pub fn pid(mut self, pid: u32) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    /// Only windows mostly on `monitor`, see [`Window::current_monitor`].
+    pub fn monitor(mut self, monitor: &Monitor) -> Self {
+        self.monitor = Some(monitor.clone());
+        self
+    }
+
+    pub fn minimized(mut self, minimized: bool) -> Self {
+        self.minimized = Some(minimized);
+        self
+    }
+
+    /// Only windows that are (or are not) mapped and not minimized.
+    pub fn visible(mut self, visible: bool) -> Self {
+        self.visible = Some(visible);
+        self
+    }
+
+    pub fn window_type(mut self, window_type: WindowType) -> Self {
+        self.window_type = Some(window_type);
+        self
+    }
+
+    /// Only windows at least `width` x `height` large.
+    pub fn min_size(mut self, width: u32, height: u32) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    /// Only windows on the given workspace, including windows that appear on every workspace.
+    pub fn workspace(mut self, workspace: u32) -> Self {
+        self.workspace = Some(workspace);
+        self
+    }
+
+    /// Sort the windows from the topmost to the bottommost (the default), or the other way around.
+    pub fn sort_by_z(mut self, topmost_first: bool) -> Self {
+        self.topmost_first = topmost_first;
+        self
+    }
+
+    /// The metadata the active filters need.
+    fn fields(&self) -> WindowInfoFields {
+        let mut fields = WindowInfoFields::empty();
+        fields.set(WindowInfoFields::AppName, self.app_name.is_some());
+        fields.set(WindowInfoFields::Title, self.title.is_some());
+        fields.set(WindowInfoFields::Pid, self.pid.is_some());
+        fields.set(
+            WindowInfoFields::Rect,
+            self.monitor.is_some() || self.min_size.is_some(),
+        );
+        fields.set(
+            WindowInfoFields::State,
+            self.minimized.is_some() || self.visible.is_some(),
+        );
+        fields.set(WindowInfoFields::WindowType, self.window_type.is_some());
+        fields.set(WindowInfoFields::Workspace, self.workspace.is_some());
+
+        fields
+    }
+
+    /// List the matching windows.
+    pub fn windows(&self) -> XCapResult<Vec<Window>> {
+        let impl_windows = ImplWindow::all_with(self.list_options)?;
+        let window_infos = ImplWindow::query_info(&impl_windows, self.fields())?;
+
+        // the monitor bounds in the coordinate space of the window rects,
+        // which are not scaled on every platform
+        let monitor_rects = match &self.monitor {
+            Some(_) => Monitor::all()?
+                .iter()
+                .filter_map(|monitor| {
+                    Some((
+                        monitor.id().ok()?,
+                        monitor.impl_monitor.window_space_rect().ok()?,
+                    ))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let monitor_id = match &self.monitor {
+            Some(monitor) => Some(monitor.id()?),
+            None => None,
+        };
+
+        // all_with already lists the windows from the topmost to the bottommost
+        let mut windows: Vec<Window> = impl_windows
+            .into_iter()
+            .zip(window_infos)
+            .filter(|(_, window_info)| self.matches(window_info, monitor_id, &monitor_rects))
+            .map(|(impl_window, _)| Window::new(impl_window))
+            .collect();
+
+        if !self.topmost_first {
+            windows.reverse();
+        }
+
+        Ok(windows)
+    }
+
+    fn matches(
+        &self,
+        window_info: &WindowInfo,
+        monitor_id: Option<u32>,
+        monitor_rects: &[(u32, Rect)],
+    ) -> bool {
+        if let Some(app_name) = &self.app_name
+            && !window_info
+                .app_name
+                .as_deref()
+                .is_some_and(|text| app_name.is_match(text))
+        {
+            return false;
+        }
+
+        if let Some(title) = &self.title
+            && !window_info
+                .title
+                .as_deref()
+                .is_some_and(|text| title.is_match(text))
+        {
+            return false;
+        }
+
+        if self.pid.is_some() && window_info.pid != self.pid {
+            return false;
+        }
+
+        if self.minimized.is_some() && window_info.is_minimized != self.minimized {
+            return false;
+        }
+
+        if self.visible.is_some() && window_info.is_visible != self.visible {
+            return false;
+        }
+
+        if self.window_type.is_some() && window_info.window_type != self.window_type {
+            return false;
+        }
+
+        if let Some((min_width, min_height)) = self.min_size
+            && !window_info
+                .rect
+                .is_some_and(|rect| rect.width >= min_width && rect.height >= min_height)
+        {
+            return false;
+        }
+
+        if let Some(workspace) = self.workspace
+            && !matches!(window_info.workspace, Some(None))
+            && window_info.workspace != Some(Some(workspace))
+        {
+            return false;
+        }
+
+        if let Some(monitor_id) = monitor_id {
+            // the window belongs to the monitor it overlaps the most, like current_monitor
+            let window_monitor_id = window_info.rect.and_then(|rect| {
+                monitor_rects
+                    .iter()
+                    .map(|&(id, monitor_rect)| (id, overlap_area(rect, monitor_rect)))
+                    .filter(|&(_, area)| area > 0)
+                    .max_by_key(|&(_, area)| area)
+                    .map(|(id, _)| id)
+            });
+
+            if window_monitor_id != Some(monitor_id) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_info() -> WindowInfo {
+        WindowInfo {
+            app_name: Some("Firefox".to_string()),
+            title: Some("Mozilla Firefox".to_string()),
+            pid: Some(42),
+            rect: Some(Rect {
+                x: 1800,
+                y: 100,
+                width: 800,
+                height: 600,
+            }),
+            is_minimized: Some(false),
+            is_visible: Some(true),
+            window_type: Some(WindowType::Normal),
+            workspace: Some(Some(1)),
+        }
+    }
+
+    #[test]
+    fn window_query_matches_filters() {
+        let window_info = window_info();
+
+        assert!(WindowQuery::new().matches(&window_info, None, &[]));
+        assert!(
+            WindowQuery::new()
+                .app_name("Fire")
+                .title("Mozilla")
+                .pid(42)
+                .minimized(false)
+                .min_size(800, 600)
+                .workspace(1)
+                .matches(&window_info, None, &[])
+        );
+        assert!(
+            !WindowQuery::new()
+                .title("Chrome")
+                .matches(&window_info, None, &[])
+        );
+        assert!(
+            !WindowQuery::new()
+                .min_size(801, 600)
+                .matches(&window_info, None, &[])
+        );
+        assert!(
+            !WindowQuery::new()
+                .workspace(2)
+                .matches(&window_info, None, &[])
+        );
+        assert!(!WindowQuery::new().window_type(WindowType::Dialog).matches(
+            &window_info,
+            None,
+            &[]
+        ));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn window_query_matches_regex() {
+        let window_info = window_info();
+
+        assert!(
+            WindowQuery::new()
+                .title_regex(Regex::new("(?i)^mozilla").unwrap())
+                .matches(&window_info, None, &[])
+        );
+        assert!(
+            !WindowQuery::new()
+                .app_name_regex(Regex::new("^fire").unwrap())
+                .matches(&window_info, None, &[])
+        );
+    }
+
+    #[test]
+    fn window_query_fetches_only_filtered_fields() {
+        assert_eq!(WindowQuery::new().fields(), WindowInfoFields::empty());
+        assert_eq!(
+            WindowQuery::new().title("a").min_size(1, 1).fields(),
+            WindowInfoFields::Title | WindowInfoFields::Rect
+        );
+    }
+
+    #[test]
+    fn window_query_matches_largest_overlapping_monitor() {
+        let window_info = window_info();
+        let monitor_rects = [
+            (
+                1,
+                Rect {
+                    x: 0,
+                    y: 0,
+                    width: 1920,
+                    height: 1080,
+                },
+            ),
+            (
+                2,
+                Rect {
+                    x: 1920,
+                    y: 0,
+                    width: 1920,
+                    height: 1080,
+                },
+            ),
+        ];
+
+        let window_query = WindowQuery::new();
+        assert!(window_query.matches(&window_info, Some(2), &monitor_rects));
+        assert!(!window_query.matches(&window_info, Some(1), &monitor_rects));
+    }
+}
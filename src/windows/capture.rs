@@ -123,7 +123,8 @@ pub fn capture_monitor(x: i32, y: i32, width: i32, height: i32) -> XCapResult<Rg
 }
 
 #[allow(unused)]
-pub fn capture_window(hwnd: HWND, scale_factor: f32) -> XCapResult<RgbaImage> {
+/// 返回截图以及截图是否不受其他窗口遮挡的影响
+pub fn capture_window(hwnd: HWND, scale_factor: f32) -> XCapResult<(RgbaImage, bool)> {
     let window_info = get_window_info(hwnd)?;
     unsafe {
         let rc_window = window_info.rcWindow;
@@ -185,6 +186,9 @@ pub fn capture_window(hwnd: HWND, scale_factor: f32) -> XCapResult<RgbaImage> {
             is_success = PrintWindow(hwnd, *scope_guard_hdc_mem, PRINT_WINDOW_FLAGS(4)).as_bool();
         }
 
+        // PrintWindow 让窗口自己绘制到内存 DC 中，不受遮挡影响，BitBlt 只能复制屏幕上的内容
+        let occlusion_safe = is_success;
+
         if !is_success {
             is_success = BitBlt(
                 *scope_guard_hdc_mem,
@@ -211,9 +215,11 @@ pub fn capture_window(hwnd: HWND, scale_factor: f32) -> XCapResult<RgbaImage> {
         let w = ((rc_client.right - rc_client.left) as f32 * scale_factor).floor();
         let h = ((rc_client.bottom - rc_client.top) as f32 * scale_factor).floor();
 
-        Ok(DynamicImage::ImageRgba8(image)
+        let image = DynamicImage::ImageRgba8(image)
             .crop(x as u32, y as u32, w as u32, h as u32)
-            .to_rgba8())
+            .to_rgba8();
+
+        Ok((image, occlusion_safe))
     }
 }
 
@@ -238,7 +244,7 @@ mod tests {
             let result = capture_window(hwnd, 1.0);
             assert!(result.is_ok());
 
-            let image = result.unwrap();
+            let (image, _) = result.unwrap();
             assert!(image.width() > 0);
             assert!(image.height() > 0);
         }
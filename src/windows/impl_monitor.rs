@@ -14,14 +14,15 @@ use windows::{
             MONITORINFO, MONITORINFOEXW, MonitorFromPoint,
         },
         System::{LibraryLoader::GetProcAddress, Threading::GetCurrentProcess},
-        UI::WindowsAndMessaging::MONITORINFOF_PRIMARY,
+        UI::WindowsAndMessaging::{GetCursorPos, MONITORINFOF_PRIMARY},
     },
     core::{BOOL, HRESULT, PCWSTR, s, w},
 };
 
 use crate::{
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    video_recorder::{Frame, FrameFitter, VideoRecorderOptions},
+    window::Rect,
 };
 
 use super::{
@@ -181,11 +182,18 @@ impl ImplMonitor {
         let h_monitor = unsafe { MonitorFromPoint(point, MONITOR_DEFAULTTONULL) };
 
         if h_monitor.is_invalid() {
-            return Err(XCapError::new("Not found monitor"));
+            return Err(XCapError::not_found("Not found monitor"));
         }
 
         Ok(ImplMonitor::new(h_monitor))
     }
+
+    pub fn cursor_position() -> XCapResult<(i32, i32)> {
+        let mut point = POINT::default();
+        unsafe { GetCursorPos(&mut point)? };
+
+        Ok((point.x, point.y))
+    }
 }
 
 impl ImplMonitor {
@@ -235,6 +243,16 @@ impl ImplMonitor {
         Ok(dev_mode_w.dmPelsHeight)
     }
 
+    /// 显示器和窗口都使用同一套屏幕坐标
+    pub fn window_space_rect(&self) -> XCapResult<Rect> {
+        Ok(Rect {
+            x: self.x()?,
+            y: self.y()?,
+            width: self.width()?,
+            height: self.height()?,
+        })
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
         let dev_mode_w = get_dev_mode_w(self.h_monitor)?;
         let dm_display_orientation =
@@ -303,7 +321,28 @@ impl ImplMonitor {
         capture_monitor(abs_x, abs_y, width as i32, height as i32)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
-        ImplVideoRecorder::new(self.h_monitor)
+    pub fn video_recorder(
+        &self,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        // 录制显示器时忽略跟随窗口，portal 相关的选项也只在 Wayland 下生效
+        if options.follow_cursor.is_some() {
+            return Err(XCapError::NotSupported);
+        }
+
+        ImplVideoRecorder::new(self.h_monitor, FrameFitter::new(&options))
+    }
+
+    pub fn video_recorders(
+        options: VideoRecorderOptions,
+    ) -> XCapResult<Vec<(ImplMonitor, ImplVideoRecorder, Receiver<Frame>)>> {
+        ImplMonitor::all()?
+            .into_iter()
+            .map(|impl_monitor| {
+                let (recorder, receiver) = impl_monitor.video_recorder(options.clone())?;
+
+                Ok((impl_monitor, recorder, receiver))
+            })
+            .collect()
     }
 }
@@ -30,7 +30,7 @@ use windows::{
 
 use crate::{
     XCapError, XCapResult,
-    video_recorder::{Frame, RecorderWaker},
+    video_recorder::{Frame, FrameFitter, RecorderWaker},
 };
 
 use super::utils::bgra_to_rgba;
@@ -88,11 +88,15 @@ pub struct ImplVideoRecorder {
     d3d_context: ID3D11DeviceContext,
     duplication: IDXGIOutputDuplication,
     recorder_waker: Arc<RecorderWaker>,
+    frame_fitter: FrameFitter,
     tx: SyncSender<Frame>,
 }
 
 impl ImplVideoRecorder {
-    pub fn new(h_monitor: HMONITOR) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        h_monitor: HMONITOR,
+        frame_fitter: FrameFitter,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         unsafe {
             let mut d3d_device = None;
             D3D11CreateDevice(
@@ -129,6 +133,7 @@ impl ImplVideoRecorder {
                         d3d_context,
                         duplication,
                         recorder_waker: Arc::new(RecorderWaker::new()),
+                        frame_fitter,
                         tx,
                     };
                     s.on_frame()?;
@@ -143,6 +148,7 @@ impl ImplVideoRecorder {
         let d3d_device = self.d3d_device.clone();
         let d3d_context = self.d3d_context.clone();
         let recorder_waker = self.recorder_waker.clone();
+        let frame_fitter = self.frame_fitter;
         let tx = self.tx.clone();
 
         thread::spawn(move || {
@@ -170,7 +176,7 @@ impl ImplVideoRecorder {
                                 let source_texture = resource.cast::<ID3D11Texture2D>()?;
                                 let frame =
                                     texture_to_frame(&d3d_device, &d3d_context, source_texture)?;
-                                let _ = tx.send(frame);
+                                let _ = tx.send(frame_fitter.fit(frame));
                             }
 
                             // 最后释放帧，不然获取不到当前帧的数据
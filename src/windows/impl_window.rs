@@ -1,7 +1,7 @@
 use core::slice;
-use std::{ffi::c_void, mem, ptr};
+use std::{ffi::c_void, mem, ptr, sync::mpsc::Receiver};
 
-use image::RgbaImage;
+use image::{RgbaImage, imageops};
 use widestring::U16CString;
 use windows::{
     Win32::{
@@ -18,19 +18,29 @@ use windows::{
             },
         },
         UI::WindowsAndMessaging::{
-            EnumWindows, GWL_EXSTYLE, GetClassNameW, GetForegroundWindow, GetWindowLongPtrW,
+            EnumChildWindows, EnumWindows, GA_PARENT, GW_OWNER, GWL_EXSTYLE, GetAncestor,
+            GetClassNameW, GetDesktopWindow, GetForegroundWindow, GetWindow, GetWindowLongPtrW,
             GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow,
-            IsWindowVisible, IsZoomed, WINDOW_EX_STYLE, WS_EX_TOOLWINDOW,
+            IsWindowVisible, IsZoomed, WINDOW_EX_STYLE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST,
         },
     },
     core::{BOOL, HSTRING, PCWSTR},
 };
 
-use crate::error::XCapResult;
+use crate::{
+    error::{XCapError, XCapResult},
+    video_recorder::{Frame, VideoRecorderOptions},
+    window::{
+        AppInfo, FrameExtents, PidSource, Rect, WindowCapture, WindowCaptureOptions,
+        WindowListOptions, WindowState, WindowType, Workspace, to_thumbnail,
+    },
+    window_query::{WindowInfo, WindowInfoFields},
+};
 
 use super::{
     capture::capture_window,
     impl_monitor::ImplMonitor,
+    impl_video_recorder::ImplVideoRecorder,
     utils::{get_process_is_dpi_awareness, get_window_info, open_process},
 };
 
@@ -176,17 +186,22 @@ extern "system" fn enum_all_windows(hwnd: HWND, state: LPARAM) -> BOOL {
     }
 }
 
-fn get_window_title(hwnd: HWND) -> XCapResult<String> {
+fn get_window_title_wide(hwnd: HWND) -> U16CString {
     unsafe {
         let text_length = GetWindowTextLengthW(hwnd);
         let mut wide_buffer = vec![0u16; (text_length + 1) as usize];
         GetWindowTextW(hwnd, &mut wide_buffer);
-        let window_title = U16CString::from_vec_truncate(wide_buffer).to_string()?;
 
-        Ok(window_title)
+        U16CString::from_vec_truncate(wide_buffer)
     }
 }
 
+fn get_window_title(hwnd: HWND) -> XCapResult<String> {
+    let window_title = get_window_title_wide(hwnd).to_string()?;
+
+    Ok(window_title)
+}
+
 #[derive(Debug, Default)]
 struct LangCodePage {
     pub w_language: u16,
@@ -339,6 +354,83 @@ impl ImplWindow {
 
         Ok(impl_windows)
     }
+
+    pub fn all_with(options: WindowListOptions) -> XCapResult<Vec<ImplWindow>> {
+        if options != WindowListOptions::default() {
+            return Err(XCapError::NotSupported);
+        }
+
+        ImplWindow::all()
+    }
+
+    pub fn workspaces() -> XCapResult<Vec<Workspace>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn query_info(
+        impl_windows: &[ImplWindow],
+        fields: WindowInfoFields,
+    ) -> XCapResult<Vec<WindowInfo>> {
+        Ok(impl_windows
+            .iter()
+            .map(|impl_window| WindowInfo::fetch(impl_window, fields))
+            .collect())
+    }
+
+    pub fn from_id(id: u32) -> XCapResult<ImplWindow> {
+        let hwnd = HWND(id as isize as *mut c_void);
+
+        unsafe {
+            if !IsWindow(Some(hwnd)).as_bool() {
+                return Err(XCapError::not_found(format!("Not found window {id}")));
+            }
+        }
+
+        Ok(ImplWindow::new(hwnd))
+    }
+
+    pub fn focused() -> XCapResult<ImplWindow> {
+        let hwnd = unsafe { GetForegroundWindow() };
+
+        if hwnd.is_invalid() {
+            return Err(XCapError::not_found("Not found focused window"));
+        }
+
+        Ok(ImplWindow::new(hwnd))
+    }
+
+    pub fn from_pid(pid: u32) -> XCapResult<Vec<ImplWindow>> {
+        let impl_windows = ImplWindow::all()?
+            .into_iter()
+            .filter(|impl_window| get_window_pid(impl_window.hwnd) == pid)
+            .collect();
+
+        Ok(impl_windows)
+    }
+
+    pub fn from_point(x: i32, y: i32) -> XCapResult<ImplWindow> {
+        // EnumWindows 按照 Z 顺序从最顶层开始遍历，第一个命中的就是最上层的窗口
+        for impl_window in ImplWindow::all()? {
+            if unsafe { IsIconic(impl_window.hwnd).as_bool() } {
+                continue;
+            }
+
+            let rc_window = match get_window_info(impl_window.hwnd) {
+                Ok(window_info) => window_info.rcWindow,
+                Err(_) => continue,
+            };
+
+            if x >= rc_window.left
+                && x < rc_window.right
+                && y >= rc_window.top
+                && y < rc_window.bottom
+            {
+                return Ok(impl_window);
+            }
+        }
+
+        Err(XCapError::not_found("Not found window"))
+    }
 }
 
 impl ImplWindow {
@@ -355,10 +447,26 @@ impl ImplWindow {
         get_app_name(self.pid()?)
     }
 
+    pub fn pid_source(&self) -> XCapResult<PidSource> {
+        Ok(PidSource::Native)
+    }
+
+    pub fn app_info(&self) -> XCapResult<AppInfo> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn title(&self) -> XCapResult<String> {
         get_window_title(self.hwnd)
     }
 
+    pub fn title_lossy(&self) -> XCapResult<String> {
+        Ok(get_window_title_wide(self.hwnd).to_string_lossy())
+    }
+
+    pub fn icon(&self, _preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn current_monitor(&self) -> XCapResult<ImplMonitor> {
         let h_monitor = unsafe { MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONEAREST) };
 
@@ -405,6 +513,43 @@ impl ImplWindow {
         Ok((window_info.rcClient.bottom - window_info.rcClient.top) as u32)
     }
 
+    pub fn frame_extents(&self) -> XCapResult<FrameExtents> {
+        let window_info = get_window_info(self.hwnd)?;
+
+        Ok(FrameExtents {
+            left: (window_info.rcClient.left - window_info.rcWindow.left) as u32,
+            right: (window_info.rcWindow.right - window_info.rcClient.right) as u32,
+            top: (window_info.rcClient.top - window_info.rcWindow.top) as u32,
+            bottom: (window_info.rcWindow.bottom - window_info.rcClient.bottom) as u32,
+        })
+    }
+
+    pub fn shadow_extents(&self) -> XCapResult<FrameExtents> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn outer_rect(&self) -> XCapResult<Rect> {
+        let window_info = get_window_info(self.hwnd)?;
+
+        Ok(Rect {
+            x: window_info.rcWindow.left,
+            y: window_info.rcWindow.top,
+            width: (window_info.rcWindow.right - window_info.rcWindow.left) as u32,
+            height: (window_info.rcWindow.bottom - window_info.rcWindow.top) as u32,
+        })
+    }
+
+    pub fn inner_rect(&self) -> XCapResult<Rect> {
+        let window_info = get_window_info(self.hwnd)?;
+
+        Ok(Rect {
+            x: window_info.rcClient.left,
+            y: window_info.rcClient.top,
+            width: (window_info.rcClient.right - window_info.rcClient.left) as u32,
+            height: (window_info.rcClient.bottom - window_info.rcClient.top) as u32,
+        })
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         unsafe { Ok(IsIconic(self.hwnd).as_bool()) }
     }
@@ -417,7 +562,159 @@ impl ImplWindow {
         unsafe { Ok(GetForegroundWindow() == self.hwnd) }
     }
 
+    pub fn workspace(&self) -> XCapResult<Option<u32>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_override_redirect(&self) -> XCapResult<bool> {
+        Ok(false)
+    }
+
+    pub fn is_managed(&self) -> XCapResult<bool> {
+        Ok(true)
+    }
+
+    pub fn parent(&self) -> XCapResult<Option<ImplWindow>> {
+        unsafe {
+            // 顶层窗口的父窗口是桌面窗口
+            let parent = GetAncestor(self.hwnd, GA_PARENT);
+            if parent.is_invalid() || parent == GetDesktopWindow() {
+                return Ok(None);
+            }
+
+            Ok(Some(ImplWindow::new(parent)))
+        }
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        // 没有所有者窗口时 GetWindow 返回错误
+        let owner = unsafe { GetWindow(self.hwnd, GW_OWNER) };
+
+        Ok(owner.ok().map(ImplWindow::new))
+    }
+
+    pub fn children(&self) -> XCapResult<Vec<ImplWindow>> {
+        let hwnds_mut_ptr: *mut Vec<HWND> = Box::into_raw(Box::default());
+
+        let hwnds = unsafe {
+            // EnumChildWindows 会递归遍历所有后代窗口，只保留直接子窗口
+            let _ = EnumChildWindows(
+                Some(self.hwnd),
+                Some(enum_all_windows),
+                LPARAM(hwnds_mut_ptr as isize),
+            );
+            Box::from_raw(hwnds_mut_ptr)
+        };
+
+        let children = hwnds
+            .iter()
+            .filter(|&&hwnd| unsafe { GetAncestor(hwnd, GA_PARENT) } == self.hwnd)
+            .map(|&hwnd| ImplWindow::new(hwnd))
+            .collect();
+
+        Ok(children)
+    }
+
+    pub fn state(&self) -> XCapResult<WindowState> {
+        let mut window_state = WindowState::empty();
+
+        unsafe {
+            if IsIconic(self.hwnd).as_bool() {
+                window_state |= WindowState::Hidden;
+            }
+
+            if IsZoomed(self.hwnd).as_bool() {
+                window_state |= WindowState::MaximizedHorz | WindowState::MaximizedVert;
+            }
+
+            let gwl_ex_style = WINDOW_EX_STYLE(GetWindowLongPtrW(self.hwnd, GWL_EXSTYLE) as u32);
+
+            if gwl_ex_style.contains(WS_EX_TOPMOST) {
+                window_state |= WindowState::Above;
+            }
+
+            if gwl_ex_style.contains(WS_EX_TOOLWINDOW) {
+                window_state |= WindowState::SkipTaskbar;
+            }
+        }
+
+        Ok(window_state)
+    }
+
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
+        let window_capture = self.capture(WindowCaptureOptions::default())?;
+
+        Ok(window_capture.image)
+    }
+
+    pub fn capture_image_with(&self, options: WindowCaptureOptions) -> XCapResult<RgbaImage> {
+        let window_capture = self.capture(options)?;
+
+        Ok(window_capture.image)
+    }
+
+    pub fn capture_region(&self, x: u32, y: u32, width: u32, height: u32) -> XCapResult<RgbaImage> {
+        // Validate region bounds
+        let window_width = self.width()?;
+        let window_height = self.height()?;
+
+        if width > window_width
+            || height > window_height
+            || x.checked_add(width)
+                .is_none_or(|right| right > window_width)
+            || y.checked_add(height)
+                .is_none_or(|bottom| bottom > window_height)
+        {
+            return Err(XCapError::InvalidCaptureRegion(format!(
+                "Region ({x}, {y}, {width}, {height}) is outside window bounds (0, 0, {window_width}, {window_height})"
+            )));
+        }
+
+        // PrintWindow 只能绘制整个窗口，截图后再裁剪；截图按窗口的 dpi 缩放，区域也需要同样缩放
+        let scale_factor = self.capture_scale_factor()?;
+        let (image, _) = capture_window(self.hwnd, scale_factor)?;
+
+        let scale = |value: u32| (value as f32 * scale_factor).round() as u32;
+        let x = scale(x).min(image.width());
+        let y = scale(y).min(image.height());
+        let width = scale(width).min(image.width() - x);
+        let height = scale(height).min(image.height() - y);
+
+        Ok(imageops::crop_imm(&image, x, y, width, height).to_image())
+    }
+
+    pub fn thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        let image = self.capture_image()?;
+
+        to_thumbnail(&image, max_width, max_height)
+    }
+
+    pub fn video_recorder(
+        &self,
+        _options: VideoRecorderOptions,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture(&self, options: WindowCaptureOptions) -> XCapResult<WindowCapture> {
+        if options != WindowCaptureOptions::default() {
+            return Err(XCapError::NotSupported);
+        }
+
+        let (image, occlusion_safe) = capture_window(self.hwnd, self.capture_scale_factor()?)?;
+
+        Ok(WindowCapture {
+            image,
+            occlusion_safe,
+        })
+    }
+
+    /// 截图相对于窗口客户区坐标的缩放比例
+    fn capture_scale_factor(&self) -> XCapResult<f32> {
         // 在win10之后，不同窗口有不同的dpi，所以可能存在截图不全或者截图有较大空白，实际窗口没有填充满图片
         // 如果窗口不感知dpi，那么就不需要缩放，如果当前进程感知dpi，那么也不需要缩放
         let scope_guard_handle =
@@ -426,12 +723,10 @@ impl ImplWindow {
         let current_process_is_dpi_awareness =
             unsafe { get_process_is_dpi_awareness(GetCurrentProcess())? };
 
-        let scale_factor = if !window_is_dpi_awareness || current_process_is_dpi_awareness {
-            1.0
+        if !window_is_dpi_awareness || current_process_is_dpi_awareness {
+            Ok(1.0)
         } else {
-            self.current_monitor()?.scale_factor()?
-        };
-
-        capture_window(self.hwnd, scale_factor)
+            self.current_monitor()?.scale_factor()
+        }
     }
 }